@@ -0,0 +1,44 @@
+//! Times forward chaining on transitive closures of growing chains
+//!
+//! Run with `cargo bench --bench saturation`. Besides the timings, this
+//! checks that semi-naive evaluation fires the transitivity rule exactly
+//! once per binding: a chain of `n` nodes has one binding per ordered
+//! triple of nodes, so `n choose 3` firings in total.
+
+use std::time::Instant;
+
+use n3proof::{create_proof_engine, parse_n3, Formula, ParseOptions, Rule};
+
+fn parse(input: &str) -> Formula {
+    let document = format!("@prefix : <http://example.org/> .\n{}", input);
+    parse_n3(document.as_bytes(), ParseOptions::default()).expect("document should parse")
+}
+
+fn chain(nodes: usize) -> Formula {
+    let edges: String = (1..nodes)
+        .map(|i| format!(":n{} :ancestor :n{} .\n", i - 1, i))
+        .collect();
+    parse(&edges)
+}
+
+fn main() {
+    println!("{:>6} {:>8} {:>10} {:>12}", "nodes", "facts", "firings", "time");
+    for nodes in [25, 50, 100, 150] {
+        let mut engine = create_proof_engine();
+        engine.add_axiom(chain(nodes));
+        engine.add_rule(Rule::new(
+            "transitivity",
+            vec![parse("?x :ancestor ?y . ?y :ancestor ?z .")],
+            parse("?x :ancestor ?z ."),
+        ));
+
+        let started = Instant::now();
+        engine.saturate().expect("saturation should succeed");
+        let elapsed = started.elapsed();
+
+        let stats = engine.stats();
+        assert_eq!(engine.fact_count(), nodes * (nodes - 1) / 2);
+        assert_eq!(stats.rule_firings, nodes * (nodes - 1) * (nodes - 2) / 6);
+        println!("{:>6} {:>8} {:>10} {:>12.2?}", nodes, engine.fact_count(), stats.rule_firings, elapsed);
+    }
+}
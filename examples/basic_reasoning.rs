@@ -110,9 +110,11 @@ fn main() {
     
     // Apply the rule
     match engine.apply_rule(rule_idx, &[knows_idx]) {
-        Ok(result_idx) => {
+        Ok(result_indices) => {
             println!("Successfully applied rule and derived new knowledge:");
-            println!("  Result {}: Bob knows Alice", result_idx);
+            for result_idx in result_indices {
+                println!("  Result {}: Bob knows Alice", result_idx);
+            }
         },
         Err(e) => {
             println!("Failed to apply rule: {}", e);
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, FixedOffset};
+use oxrdf::{Literal, NamedNode};
+
+use crate::{builtin_list, builtin_log, builtin_math, builtin_string, builtin_time};
+use crate::error::BuiltinError;
+use crate::model::{Formula, Term};
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// A predicate whose statements are computed rather than looked up
+///
+/// When a rule premise or a goal uses a predicate registered as a builtin,
+/// the reasoner calls [`Builtin::evaluate`] instead of matching the
+/// statement against the knowledge base.
+///
+/// Any `Fn(&Term, &Term, &Substitution) -> Result<Vec<Substitution>, BuiltinError>`
+/// closure that is `Send + Sync` is a builtin.
+pub trait Builtin: Send + Sync {
+    /// Evaluates the statement `subject <builtin> object`
+    ///
+    /// `subject` and `object` have `binding` applied already, so any
+    /// variable left in them is unbound. Returns one extension of `binding`
+    /// per way the statement holds, which is none if it does not hold.
+    /// Return [`BuiltinError::InsufficientlyBound`] to be evaluated again
+    /// once more variables are bound.
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError>;
+
+    /// Evaluates the statement with access to the engine evaluating it
+    ///
+    /// The reasoner always calls this method. Builtins that need more than
+    /// their arguments, such as `list:map`, which calls another builtin,
+    /// override it; by default it calls [`Builtin::evaluate`].
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let _ = context;
+        self.evaluate(subject, object, binding)
+    }
+}
+
+/// Statements that builtins scoped over the knowledge base search
+pub(crate) trait KnowledgeBase {
+    /// Finds every extension of `binding` under which all statements of `pattern` hold
+    fn query(
+        &self,
+        context: &BuiltinContext<'_>,
+        pattern: &Formula,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError>;
+}
+
+/// What a builtin can see of the engine evaluating it
+#[derive(Clone, Copy)]
+pub struct BuiltinContext<'a> {
+    builtins: &'a BuiltinRegistry,
+    knowledge_base: Option<&'a dyn KnowledgeBase>,
+    local_time: Option<DateTime<FixedOffset>>,
+}
+
+impl<'a> BuiltinContext<'a> {
+    /// Creates a context in which the given builtins are known, without a knowledge base
+    pub fn new(builtins: &'a BuiltinRegistry) -> Self {
+        BuiltinContext { builtins, knowledge_base: None, local_time: None }
+    }
+
+    /// Lets builtins evaluated in this context search a knowledge base
+    pub(crate) fn with_knowledge_base(mut self, knowledge_base: &'a dyn KnowledgeBase) -> Self {
+        self.knowledge_base = Some(knowledge_base);
+        self
+    }
+
+    /// Sets the time `time:localTime` gives in this context
+    pub(crate) fn with_local_time(mut self, local_time: Option<DateTime<FixedOffset>>) -> Self {
+        self.local_time = local_time;
+        self
+    }
+
+    /// The builtins known to the engine
+    pub fn builtins(&self) -> &'a BuiltinRegistry {
+        self.builtins
+    }
+
+    /// Returns true if builtins can search the engine's knowledge base
+    pub fn has_knowledge_base(&self) -> bool {
+        self.knowledge_base.is_some()
+    }
+
+    /// The time the engine read from its clock when the current run started, if there is one
+    pub fn local_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.local_time
+    }
+
+    /// Finds every extension of `binding` under which all statements of `pattern` are facts of the engine
+    ///
+    /// Builtins in the pattern are evaluated and its blank nodes match any
+    /// term without being bound. Outside an engine there are no facts to
+    /// search, so `builtin` is reported as insufficiently bound.
+    pub fn query(&self, builtin: &str, pattern: &Formula, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        match self.knowledge_base {
+            Some(knowledge_base) => knowledge_base.query(self, pattern, binding),
+            None => Err(BuiltinError::insufficiently_bound(builtin)),
+        }
+    }
+}
+
+/// Runs `evaluate` in the context builtins are evaluated in outside an engine
+///
+/// The standard builtins are known and there is no knowledge base. The
+/// registry is built the first time it is needed and shared from then on.
+pub(crate) fn standard_context<T>(evaluate: impl FnOnce(&BuiltinContext<'_>) -> T) -> T {
+    static STANDARD: OnceLock<BuiltinRegistry> = OnceLock::new();
+    evaluate(&BuiltinContext::new(STANDARD.get_or_init(BuiltinRegistry::standard)))
+}
+
+impl fmt::Debug for BuiltinContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuiltinContext")
+            .field("builtins", self.builtins)
+            .field("knowledge_base", &self.has_knowledge_base())
+            .field("local_time", &self.local_time)
+            .finish()
+    }
+}
+
+impl<F> Builtin for F
+where
+    F: Fn(&Term, &Term, &Substitution) -> Result<Vec<Substitution>, BuiltinError> + Send + Sync,
+{
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        self(subject, object, binding)
+    }
+}
+
+/// Builtins by predicate IRI
+#[derive(Clone, Default)]
+pub struct BuiltinRegistry {
+    builtins: HashMap<String, Arc<dyn Builtin>>,
+}
+
+impl BuiltinRegistry {
+    /// Creates a registry without any builtins
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the standard N3 builtins
+    ///
+    /// These are the builtins of the `math:`, `string:`, `list:`, `log:` and `time:` namespaces.
+    pub fn standard() -> Self {
+        let mut registry = BuiltinRegistry::new();
+        builtin_math::register(&mut registry);
+        builtin_string::register(&mut registry);
+        builtin_list::register(&mut registry);
+        builtin_log::register(&mut registry);
+        builtin_time::register(&mut registry);
+        registry
+    }
+
+    /// Registers a builtin for a predicate IRI, replacing any previous one
+    pub fn register(&mut self, iri: &str, builtin: impl Builtin + 'static) {
+        self.builtins.insert(iri.to_string(), Arc::new(builtin));
+    }
+
+    /// Returns the builtin registered for a predicate IRI
+    pub fn get(&self, iri: &str) -> Option<&Arc<dyn Builtin>> {
+        self.builtins.get(iri)
+    }
+
+    /// Returns the builtin a statement's predicate refers to, if any
+    pub fn for_predicate(&self, predicate: &Term) -> Option<&Arc<dyn Builtin>> {
+        match predicate {
+            Term::Iri(iri) => self.get(iri.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a builtin is registered for the IRI
+    pub fn contains(&self, iri: &str) -> bool {
+        self.builtins.contains_key(iri)
+    }
+
+    /// Returns the number of registered builtins
+    pub fn len(&self) -> usize {
+        self.builtins.len()
+    }
+
+    /// Returns true if no builtin is registered
+    pub fn is_empty(&self) -> bool {
+        self.builtins.is_empty()
+    }
+
+    /// Iterates over the registered IRIs in no particular order
+    pub fn iris(&self) -> impl Iterator<Item = &str> {
+        self.builtins.keys().map(String::as_str)
+    }
+}
+
+impl fmt::Debug for BuiltinRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iris: Vec<&str> = self.iris().collect();
+        iris.sort_unstable();
+        f.debug_set().entries(iris).finish()
+    }
+}
+
+/// Returns the members of a list argument
+///
+/// An unbound argument is [`BuiltinError::InsufficientlyBound`] and any
+/// other term that is not a list is an invalid argument.
+pub(crate) fn list_arguments<'a>(builtin: &str, term: &'a Term) -> Result<&'a [Term], BuiltinError> {
+    match term {
+        Term::List(items) => Ok(items),
+        Term::Variable(_) => Err(BuiltinError::insufficiently_bound(builtin)),
+        _ => Err(BuiltinError::invalid_argument(builtin, "expected a list")),
+    }
+}
+
+/// An `xsd:integer` literal, as returned by counting builtins such as `string:length`
+pub(crate) fn integer_term(value: usize) -> Term {
+    Term::Literal(Literal::new_typed_literal(value.to_string(), NamedNode::new_unchecked(vocab::XSD_INTEGER)))
+}
+
+/// Unifies the object of a builtin with the value it computed
+pub(crate) fn unify_result(object: &Term, value: Term, binding: &Substitution) -> Vec<Substitution> {
+    binding.unify_terms(object, &value).into_iter().collect()
+}
+
+/// The solutions of a builtin that only tests its arguments
+pub(crate) fn holds(condition: bool, binding: &Substitution) -> Vec<Substitution> {
+    if condition {
+        vec![binding.clone()]
+    } else {
+        Vec::new()
+    }
+}
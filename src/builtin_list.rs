@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+
+use num_traits::ToPrimitive;
+
+use crate::builtin::{integer_term, list_arguments, standard_context, unify_result, Builtin, BuiltinContext, BuiltinRegistry};
+use crate::builtin_math::Number;
+use crate::error::BuiltinError;
+use crate::model::Term;
+use crate::unify::Substitution;
+use crate::vocab;
+
+fn iri(local: &str) -> String {
+    format!("{}{}", vocab::LIST, local)
+}
+
+/// Reads a subject list of exactly `N` arguments
+fn arguments<'a, const N: usize>(builtin: &str, subject: &'a Term) -> Result<&'a [Term; N], BuiltinError> {
+    let items = list_arguments(builtin, subject)?;
+    <&[Term; N]>::try_from(items).map_err(|_| {
+        BuiltinError::invalid_argument(builtin, format!("expected a list of {} arguments, got {}", N, items.len()))
+    })
+}
+
+/// Requires every member of a list to be bound, as sorting and comparing them does
+fn ground<'a>(builtin: &str, items: &'a [Term]) -> Result<&'a [Term], BuiltinError> {
+    if !items.iter().all(Term::is_ground) {
+        return Err(BuiltinError::insufficiently_bound(builtin));
+    }
+    Ok(items)
+}
+
+/// Reads a bound non-negative integer, returning `None` for any other term
+fn index(builtin: &str, term: &Term) -> Result<Option<usize>, BuiltinError> {
+    match Number::from_term(builtin, term)? {
+        Some(Number::Integer(value)) => Ok(value.to_usize()),
+        _ => Ok(None),
+    }
+}
+
+/// Orders terms for `list:sort`
+///
+/// Numbers, i.e. literals of a numeric datatype but not strings that look
+/// like one, come first, in numeric order, followed by other literals, IRIs,
+/// blank nodes, lists, formulas and variables. Terms of the same kind are
+/// ordered by their lexical form, lists element by element.
+fn compare_terms(a: &Term, b: &Term) -> Ordering {
+    let number = |term: &Term| match term {
+        Term::Literal(literal) if literal.datatype().as_str() == vocab::XSD_STRING => None,
+        _ => Number::from_term("", term).ok().flatten(),
+    };
+    let rank = |term: &Term, number: &Option<Number>| match term {
+        Term::Literal(_) if number.is_some() => 0,
+        Term::Literal(_) => 1,
+        Term::Iri(_) => 2,
+        Term::BlankNode(_) => 3,
+        Term::List(_) => 4,
+        Term::Formula(_) => 5,
+        Term::Variable(_) => 6,
+    };
+    let (number_a, number_b) = (number(a), number(b));
+    let by_kind = rank(a, &number_a).cmp(&rank(b, &number_b));
+    let by_number = match (&number_a, &number_b) {
+        (Some(x), Some(y)) => x.compare(y).unwrap_or(Ordering::Equal),
+        _ => Ordering::Equal,
+    };
+    let by_form = match (a, b) {
+        (Term::Literal(x), Term::Literal(y)) => (x.value(), x.datatype().as_str(), x.language())
+            .cmp(&(y.value(), y.datatype().as_str(), y.language())),
+        (Term::Iri(x), Term::Iri(y)) => x.as_str().cmp(y.as_str()),
+        (Term::BlankNode(x), Term::BlankNode(y)) => x.as_str().cmp(y.as_str()),
+        (Term::List(x), Term::List(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare_terms(x, y))
+            .find(|order| order.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Term::Formula(x), Term::Formula(y)) => x.canonical_form().cmp(&y.canonical_form()),
+        (Term::Variable(x), Term::Variable(y)) => x.cmp(y),
+        _ => Ordering::Equal,
+    };
+    by_kind.then(by_number).then(by_form)
+}
+
+/// Finds every way of splitting `target` into consecutive parts unifying with `parts`
+///
+/// A part that is already a list takes as many members as it has; any other
+/// part, typically a variable, is tried with every possible length.
+fn splits(parts: &[Term], target: &[Term], binding: &Substitution, solutions: &mut Vec<Substitution>) {
+    let Some((part, rest)) = parts.split_first() else {
+        if target.is_empty() {
+            solutions.push(binding.clone());
+        }
+        return;
+    };
+    let part = binding.apply_term(part);
+    let lengths = match &part {
+        Term::List(items) if items.len() <= target.len() => items.len()..=items.len(),
+        Term::List(_) => return,
+        _ if rest.is_empty() => target.len()..=target.len(),
+        _ => 0..=target.len(),
+    };
+    for length in lengths {
+        let (prefix, suffix) = target.split_at(length);
+        if let Some(extended) = binding.unify_terms(&part, &Term::List(prefix.to_vec())) {
+            splits(rest, suffix, &extended, solutions);
+        }
+    }
+}
+
+/// `(list predicate) list:map results` applies a builtin predicate to every member of a list
+///
+/// Each member is the subject of one call and the results are collected in
+/// order. A predicate with several results for a member gives one list
+/// for every combination of them.
+struct Map;
+
+impl Map {
+    const RESULT: &'static str = "list:map result";
+}
+
+impl Builtin for Map {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        standard_context(|context| self.evaluate_in(context, subject, object, binding))
+    }
+
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let name = iri("map");
+        if !subject.is_ground() {
+            return Err(BuiltinError::insufficiently_bound(&name));
+        }
+        let [list, predicate] = arguments(&name, subject)?;
+        let items = list_arguments(&name, list)?;
+        let builtin = context
+            .builtins()
+            .for_predicate(predicate)
+            .ok_or_else(|| BuiltinError::invalid_argument(&name, "the predicate to map is not a builtin"))?;
+
+        let result = Term::Variable(Map::RESULT.to_string());
+        let mut lists: Vec<Vec<Term>> = vec![Vec::new()];
+        for item in items {
+            let values: Vec<Term> = builtin
+                .evaluate_in(context, item, &result, &Substitution::new())?
+                .iter()
+                .filter_map(|solution| solution.get(Map::RESULT).cloned())
+                .collect();
+            lists = lists
+                .iter()
+                .flat_map(|list| {
+                    values.iter().map(move |value| {
+                        let mut extended = list.clone();
+                        extended.push(value.clone());
+                        extended
+                    })
+                })
+                .collect();
+        }
+        Ok(lists
+            .into_iter()
+            .flat_map(|list| unify_result(object, Term::List(list), binding))
+            .collect())
+    }
+}
+
+/// Registers a function from the subject list to the object
+fn register_function(
+    registry: &mut BuiltinRegistry,
+    local: &str,
+    function: fn(&str, &[Term]) -> Result<Option<Term>, BuiltinError>,
+) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = function(&name, list_arguments(&name, subject)?)?;
+        Ok(value.map(|value| unify_result(object, value, binding)).unwrap_or_default())
+    });
+}
+
+/// Registers the builtins of the `list:` namespace
+///
+/// Lists are N3 collections written `( ... )`. Builtins that can hold in
+/// several ways, such as `list:member`, or `list:append` with an unbound
+/// part, give one solution for each.
+pub(crate) fn register(registry: &mut BuiltinRegistry) {
+    let name = iri("append");
+    registry.register(&iri("append"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let parts = list_arguments(&name, subject)?;
+        let mut solutions = Vec::new();
+        if let Term::List(target) = object {
+            splits(parts, target, binding, &mut solutions);
+            return Ok(solutions);
+        }
+        let mut appended = Vec::new();
+        for part in parts {
+            match part {
+                Term::List(items) => appended.extend(items.iter().cloned()),
+                _ if !part.is_ground() => return Err(BuiltinError::insufficiently_bound(&name)),
+                _ => return Ok(Vec::new()),
+            }
+        }
+        Ok(unify_result(object, Term::List(appended), binding))
+    });
+
+    let name = iri("member");
+    registry.register(&iri("member"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let items = list_arguments(&name, subject)?;
+        Ok(items.iter().filter_map(|item| binding.unify_terms(object, item)).collect())
+    });
+
+    let name = iri("in");
+    registry.register(&iri("in"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let items = list_arguments(&name, object)?;
+        Ok(items.iter().filter_map(|item| binding.unify_terms(subject, item)).collect())
+    });
+
+    let name = iri("iterate");
+    registry.register(&iri("iterate"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let items = list_arguments(&name, subject)?;
+        Ok(items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| binding.unify_terms(object, &Term::List(vec![integer_term(i), item.clone()])))
+            .collect())
+    });
+
+    let name = iri("memberAt");
+    registry.register(&iri("memberAt"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let [list, position] = arguments(&name, subject)?;
+        let items = list_arguments(&name, list)?;
+        if position.is_ground() {
+            let member = index(&name, position)?.and_then(|i| items.get(i));
+            return Ok(member.map(|member| unify_result(object, member.clone(), binding)).unwrap_or_default());
+        }
+        Ok(items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| binding.unify_terms(position, &integer_term(i))?.unify_terms(object, item))
+            .collect())
+    });
+
+    registry.register(&iri("map"), Map);
+
+    register_function(registry, "first", |_, items| Ok(items.first().cloned()));
+    register_function(registry, "rest", |_, items| Ok(items.split_first().map(|(_, rest)| Term::List(rest.to_vec()))));
+    register_function(registry, "last", |_, items| Ok(items.last().cloned()));
+    register_function(registry, "length", |_, items| Ok(Some(integer_term(items.len()))));
+    register_function(registry, "remove", |name, items| {
+        let [list, removed] = <&[Term; 2]>::try_from(items)
+            .map_err(|_| BuiltinError::invalid_argument(name, "expected a list and a member to remove"))?;
+        let items = ground(name, list_arguments(name, list)?)?;
+        if !removed.is_ground() {
+            return Err(BuiltinError::insufficiently_bound(name));
+        }
+        Ok(Some(Term::List(items.iter().filter(|item| *item != removed).cloned().collect())))
+    });
+    register_function(registry, "sort", |name, items| {
+        let mut sorted = ground(name, items)?.to_vec();
+        sorted.sort_by(compare_terms);
+        Ok(Some(Term::List(sorted)))
+    });
+    register_function(registry, "unique", |name, items| {
+        let mut unique: Vec<Term> = Vec::new();
+        for item in ground(name, items)? {
+            if !unique.contains(item) {
+                unique.push(item.clone());
+            }
+        }
+        Ok(Some(Term::List(unique)))
+    });
+}
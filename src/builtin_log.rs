@@ -0,0 +1,386 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use oxrdf::{Literal, NamedNode};
+use sha2::{Digest, Sha256};
+
+use crate::builtin::{holds, list_arguments, standard_context, unify_result, Builtin, BuiltinContext, BuiltinRegistry};
+use crate::builtin_time::FixedClock;
+use crate::error::{BuiltinError, Error};
+use crate::model::{Formula, Statement, Term};
+use crate::program::Program;
+use crate::reasoner::{query_formula, ProofEngine};
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// Prefix of the IRIs minted by `log:skolem`
+const SKOLEM_PREFIX: &str = "urn:n3proof:skolem:";
+
+fn iri(local: &str) -> String {
+    format!("{}{}", vocab::LOG, local)
+}
+
+fn iri_term(iri: &str) -> Term {
+    Term::Iri(NamedNode::new_unchecked(iri))
+}
+
+/// Reads a bound formula argument, returning `None` for any other term
+fn formula<'a>(builtin: &str, term: &'a Term) -> Result<Option<&'a Formula>, BuiltinError> {
+    match term {
+        Term::Formula(formula) => Ok(Some(formula)),
+        Term::Variable(_) => Err(BuiltinError::insufficiently_bound(builtin)),
+        _ => Ok(None),
+    }
+}
+
+/// Finds every way the statements of `pattern` hold in a scope
+///
+/// The scope is a quoted formula, or the engine's knowledge base when it is
+/// a variable, as in `?SCOPE log:notIncludes { ... }`. Returns `None` for
+/// any other scope, which makes the builtin fail.
+fn search(
+    context: &BuiltinContext<'_>,
+    builtin: &str,
+    scope: &Term,
+    pattern: &Formula,
+    binding: &Substitution,
+) -> Result<Option<Vec<Substitution>>, BuiltinError> {
+    match scope {
+        Term::Formula(scope) => query_formula(context, scope, pattern, binding).map(Some),
+        Term::Variable(_) => context.query(builtin, pattern, binding).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// The formulas a builtin searches the knowledge base for, if it does
+///
+/// Used to stratify rules, since what such a search finds depends on which
+/// facts have been derived so far: `log:includes`, `log:notIncludes`,
+/// `log:collectAllIn` and `log:forAllIn` with a scope other than a quoted
+/// formula.
+pub(crate) fn scoped_query(statement: &Statement) -> Option<Vec<&Formula>> {
+    let Term::Iri(predicate) = &statement.predicate else {
+        return None;
+    };
+    let local = predicate.as_str().strip_prefix(vocab::LOG)?;
+    let (scope, arguments) = match local {
+        "includes" | "notIncludes" => (&statement.subject, &statement.object),
+        "collectAllIn" | "forAllIn" => (&statement.object, &statement.subject),
+        _ => return None,
+    };
+    if matches!(scope, Term::Formula(_)) {
+        return None;
+    }
+    Some(match arguments {
+        Term::Formula(formula) => vec![&**formula],
+        Term::List(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Term::Formula(formula) => Some(&**formula),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+/// `scope log:includes pattern` and `scope log:notIncludes pattern`
+///
+/// `log:includes` gives one solution per way the pattern holds in the scope,
+/// binding its variables; `log:notIncludes` holds when there is none, which
+/// is negation as failure scoped to the formula or knowledge base searched.
+struct Includes {
+    negated: bool,
+}
+
+impl Builtin for Includes {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        standard_context(|context| self.evaluate_in(context, subject, object, binding))
+    }
+
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let name = iri(if self.negated { "notIncludes" } else { "includes" });
+        let Some(pattern) = formula(&name, object)? else {
+            return Ok(Vec::new());
+        };
+        let Some(found) = search(context, &name, subject, pattern, binding)? else {
+            return Ok(Vec::new());
+        };
+        if self.negated {
+            Ok(holds(found.is_empty(), binding))
+        } else {
+            Ok(found)
+        }
+    }
+}
+
+/// `(template clause list) log:collectAllIn scope` collects the template for every solution of the clause
+///
+/// The list holds one instance of the template per way the clause holds in
+/// the scope, in the order they are found.
+struct CollectAllIn;
+
+impl Builtin for CollectAllIn {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        standard_context(|context| self.evaluate_in(context, subject, object, binding))
+    }
+
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let name = iri("collectAllIn");
+        let [template, clause, list] = list_arguments(&name, subject)? else {
+            return Err(BuiltinError::invalid_argument(&name, "expected a template, a clause and a list"));
+        };
+        let Some(clause) = formula(&name, clause)? else {
+            return Ok(Vec::new());
+        };
+        let Some(solutions) = search(context, &name, object, clause, binding)? else {
+            return Ok(Vec::new());
+        };
+        let collected = solutions.iter().map(|solution| solution.apply_term(template)).collect();
+        Ok(unify_result(list, Term::List(collected), binding))
+    }
+}
+
+/// `(where then) log:forAllIn scope` holds if `then` holds for every solution of `where`
+struct ForAllIn;
+
+impl Builtin for ForAllIn {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        standard_context(|context| self.evaluate_in(context, subject, object, binding))
+    }
+
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let name = iri("forAllIn");
+        let [condition, consequence] = list_arguments(&name, subject)? else {
+            return Err(BuiltinError::invalid_argument(&name, "expected two formulas"));
+        };
+        let (Some(condition), Some(consequence)) = (formula(&name, condition)?, formula(&name, consequence)?) else {
+            return Ok(Vec::new());
+        };
+        let Some(solutions) = search(context, &name, object, condition, binding)? else {
+            return Ok(Vec::new());
+        };
+        for solution in solutions {
+            let consequence = solution.apply_formula(consequence);
+            match search(context, &name, object, &consequence, &solution)? {
+                Some(found) if !found.is_empty() => {}
+                _ => return Ok(Vec::new()),
+            }
+        }
+        Ok(vec![binding.clone()])
+    }
+}
+
+/// `formula log:conclusion closure` computes the deductive closure of a formula
+///
+/// The rules of the formula are applied to its facts by a nested engine that
+/// knows the same builtins, and the closure holds the formula's own
+/// statements followed by everything derived from them.
+struct Conclusion;
+
+impl Builtin for Conclusion {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        standard_context(|context| self.evaluate_in(context, subject, object, binding))
+    }
+
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let name = iri("conclusion");
+        let Some(premise) = formula(&name, subject)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut engine = ProofEngine::new();
+        engine.set_builtins(context.builtins().clone());
+        if let Some(local_time) = context.local_time() {
+            engine.set_clock(FixedClock::new(local_time));
+        }
+        engine.add_program(&Program::from_formula(premise));
+        let derived = engine.saturate().map_err(|error| match error {
+            Error::BuiltinError(error) => error,
+            other => BuiltinError::invalid_argument(&name, other.to_string()),
+        })?;
+
+        let mut closure = premise.clone();
+        for statement in derived {
+            closure.add_statement(statement);
+        }
+        Ok(unify_result(object, Term::Formula(Arc::new(closure)), binding))
+    }
+}
+
+/// Writes a term in a form that identifies it, for hashing by `log:skolem`
+///
+/// Nested formulas are written in canonical form so that formulas equal up
+/// to renaming get the same IRI; other blank nodes keep their identity.
+fn write_key(term: &Term, out: &mut String) {
+    match term {
+        Term::Iri(iri) => write!(out, "{}", iri),
+        Term::BlankNode(node) => write!(out, "{}", node),
+        Term::Literal(literal) => write!(out, "{}", literal),
+        Term::Variable(var) => write!(out, "?{}", var),
+        Term::Formula(formula) => write!(out, "{{{}}}", formula.canonical_form()),
+        Term::List(items) => {
+            out.push('(');
+            for item in items {
+                write_key(item, out);
+                out.push(' ');
+            }
+            out.push(')');
+            Ok(())
+        }
+    }
+    .expect("writing to a String cannot fail");
+}
+
+/// The class `log:rawType` gives a term
+fn raw_type(term: &Term) -> Term {
+    match term {
+        Term::Formula(_) => iri_term(&iri("Formula")),
+        Term::Literal(_) => iri_term(&iri("Literal")),
+        Term::List(_) => iri_term(vocab::RDF_LIST),
+        _ => iri_term(&iri("Other")),
+    }
+}
+
+/// Registers the builtins of the `log:` namespace
+///
+/// These inspect, combine and compare terms, in particular quoted formulas.
+/// `log:includes`, `log:notIncludes`, `log:collectAllIn` and `log:forAllIn`
+/// search a quoted formula or, given a variable as scope, the knowledge
+/// base; `log:conclusion` reasons over a formula with a nested engine.
+pub(crate) fn register(registry: &mut BuiltinRegistry) {
+    registry.register(&iri("includes"), Includes { negated: false });
+    registry.register(&iri("notIncludes"), Includes { negated: true });
+    registry.register(&iri("collectAllIn"), CollectAllIn);
+    registry.register(&iri("forAllIn"), ForAllIn);
+
+    let name = iri("conjunction");
+    registry.register(&iri("conjunction"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let mut conjunction = Formula::new();
+        for item in list_arguments(&name, subject)? {
+            let Some(part) = formula(&name, item)? else {
+                return Ok(Vec::new());
+            };
+            for statement in &part.statements {
+                conjunction.add_statement(statement.clone());
+            }
+            conjunction.universal_vars.extend(part.universal_vars.iter().cloned());
+            conjunction.existential_vars.extend(part.existential_vars.iter().cloned());
+        }
+        Ok(unify_result(object, Term::Formula(Arc::new(conjunction)), binding))
+    });
+
+    registry.register(&iri("conclusion"), Conclusion);
+
+    registry.register(&iri("equalTo"), |subject: &Term, object: &Term, binding: &Substitution| {
+        Ok(binding.unify_terms(subject, object).into_iter().collect())
+    });
+
+    let name = iri("notEqualTo");
+    registry.register(&iri("notEqualTo"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        if !subject.is_ground() || !object.is_ground() {
+            return Err(BuiltinError::insufficiently_bound(&name));
+        }
+        Ok(holds(binding.unify_terms(subject, object).is_none(), binding))
+    });
+
+    let name = iri("uri");
+    registry.register(&iri("uri"), move |subject: &Term, object: &Term, binding: &Substitution| match (subject, object) {
+        (Term::Iri(iri), _) => Ok(unify_result(object, Term::Literal(Literal::new_simple_literal(iri.as_str())), binding)),
+        (Term::Variable(_), Term::Literal(literal)) => {
+            let iri = NamedNode::new(literal.value())
+                .map_err(|error| BuiltinError::invalid_argument(&name, error.to_string()))?;
+            Ok(unify_result(subject, Term::Iri(iri), binding))
+        }
+        (Term::Variable(_), Term::Variable(_)) => Err(BuiltinError::insufficiently_bound(&name)),
+        _ => Ok(Vec::new()),
+    });
+
+    let name = iri("rawType");
+    registry.register(&iri("rawType"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        if let Term::Variable(_) = subject {
+            return Err(BuiltinError::insufficiently_bound(&name));
+        }
+        Ok(unify_result(object, raw_type(subject), binding))
+    });
+
+    let name = iri("dtlit");
+    registry.register(&iri("dtlit"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        if let Term::Literal(literal) = object {
+            let parts = Term::List(vec![
+                Term::Literal(Literal::new_simple_literal(literal.value())),
+                Term::Iri(literal.datatype().into_owned()),
+            ]);
+            return Ok(unify_result(subject, parts, binding));
+        }
+        match list_arguments(&name, subject)? {
+            [Term::Literal(lexical), Term::Iri(datatype)] => {
+                let literal = Literal::new_typed_literal(lexical.value(), datatype.clone());
+                Ok(unify_result(object, Term::Literal(literal), binding))
+            }
+            parts if parts.iter().all(Term::is_ground) => Ok(Vec::new()),
+            _ => Err(BuiltinError::insufficiently_bound(&name)),
+        }
+    });
+
+    let name = iri("langlit");
+    registry.register(&iri("langlit"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        if let Term::Literal(literal) = object {
+            let Some(language) = literal.language() else {
+                return Ok(Vec::new());
+            };
+            let parts = Term::List(vec![
+                Term::Literal(Literal::new_simple_literal(literal.value())),
+                Term::Literal(Literal::new_simple_literal(language)),
+            ]);
+            return Ok(unify_result(subject, parts, binding));
+        }
+        match list_arguments(&name, subject)? {
+            [Term::Literal(lexical), Term::Literal(language)] => {
+                let literal = Literal::new_language_tagged_literal(lexical.value(), language.value())
+                    .map_err(|error| BuiltinError::invalid_argument(&name, error.to_string()))?;
+                Ok(unify_result(object, Term::Literal(literal), binding))
+            }
+            parts if parts.iter().all(Term::is_ground) => Ok(Vec::new()),
+            _ => Err(BuiltinError::insufficiently_bound(&name)),
+        }
+    });
+
+    let name = iri("skolem");
+    registry.register(&iri("skolem"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        if !subject.is_ground() {
+            return Err(BuiltinError::insufficiently_bound(&name));
+        }
+        let mut key = String::new();
+        write_key(subject, &mut key);
+        let mut skolem = SKOLEM_PREFIX.to_string();
+        for byte in &Sha256::digest(key.as_bytes())[..16] {
+            write!(skolem, "{:02x}", byte).expect("writing to a String cannot fail");
+        }
+        Ok(unify_result(object, iri_term(&skolem), binding))
+    });
+}
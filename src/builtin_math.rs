@@ -0,0 +1,536 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, RoundingMode};
+use num_bigint::{BigInt, BigUint};
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+use oxrdf::{Literal, NamedNode};
+
+use crate::builtin::{holds, list_arguments, unify_result, BuiltinRegistry};
+use crate::builtin_time::Temporal;
+use crate::error::BuiltinError;
+use crate::model::Term;
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// Integer datatypes, which are all read as arbitrary-precision `xsd:integer`s
+const INTEGER_TYPES: &[&str] = &[
+    "integer",
+    "long",
+    "int",
+    "short",
+    "byte",
+    "nonNegativeInteger",
+    "nonPositiveInteger",
+    "positiveInteger",
+    "negativeInteger",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+];
+
+/// Largest exact integer power computed, in bits; larger ones are computed as doubles
+const MAX_POWER_BITS: u64 = 1 << 20;
+
+/// A value of one of the XSD numeric types
+///
+/// The variants are ordered by type promotion: when two numbers of
+/// different types are combined, the one earlier in the list is promoted to
+/// the type of the other, as in XPath arithmetic.
+#[derive(Debug, Clone)]
+pub(crate) enum Number {
+    Integer(BigInt),
+    Decimal(BigDecimal),
+    Float(f32),
+    Double(f64),
+}
+
+impl Number {
+    /// Reads a numeric literal, returning `None` for other terms
+    ///
+    /// Plain strings holding a number are accepted too, as cwm and EYE do.
+    /// A literal with a numeric datatype but an invalid lexical form is an
+    /// error.
+    pub(crate) fn from_term(builtin: &str, term: &Term) -> Result<Option<Number>, BuiltinError> {
+        let Term::Literal(literal) = term else {
+            return Ok(None);
+        };
+        let value = literal.value().trim();
+        let invalid = || {
+            BuiltinError::invalid_argument(builtin, format!("'{}' is not a valid {}", value, literal.datatype().as_str()))
+        };
+        let datatype = literal.datatype().as_str();
+        let Some(local) = datatype.strip_prefix("http://www.w3.org/2001/XMLSchema#") else {
+            return Ok(None);
+        };
+        let number = match local {
+            "decimal" => Number::Decimal(BigDecimal::from_str(value).map_err(|_| invalid())?),
+            "float" => Number::Float(parse_float(value).ok_or_else(invalid)? as f32),
+            "double" => Number::Double(parse_float(value).ok_or_else(invalid)?),
+            "string" if literal.language().is_none() => return Ok(Number::parse(value)),
+            _ if INTEGER_TYPES.contains(&local) => Number::Integer(parse_integer(value).ok_or_else(invalid)?),
+            _ => return Ok(None),
+        };
+        Ok(Some(number))
+    }
+
+    /// Reads a number from its lexical form, trying integer, decimal and double in turn
+    fn parse(value: &str) -> Option<Number> {
+        if let Some(integer) = parse_integer(value) {
+            return Some(Number::Integer(integer));
+        }
+        if !value.contains(['e', 'E']) {
+            if let Ok(decimal) = BigDecimal::from_str(value) {
+                return Some(Number::Decimal(decimal));
+            }
+        }
+        parse_float(value).map(Number::Double)
+    }
+
+    /// Writes the number as a literal of its type in canonical form
+    pub(crate) fn to_term(&self) -> Term {
+        let (lexical, datatype) = match self {
+            Number::Integer(value) => (value.to_string(), vocab::XSD_INTEGER),
+            Number::Decimal(value) => (decimal_lexical(value), vocab::XSD_DECIMAL),
+            Number::Float(value) => (double_lexical(f64::from(*value)), vocab::XSD_FLOAT),
+            Number::Double(value) => (double_lexical(*value), vocab::XSD_DOUBLE),
+        };
+        Term::Literal(Literal::new_typed_literal(lexical, NamedNode::new_unchecked(datatype)))
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Number::Integer(_) => 0,
+            Number::Decimal(_) => 1,
+            Number::Float(_) => 2,
+            Number::Double(_) => 3,
+        }
+    }
+
+    /// Converts the number to the type of the given rank, which must not be lower than its own
+    fn promote(&self, rank: u8) -> Number {
+        match (rank, self) {
+            (_, number) if number.rank() == rank => number.clone(),
+            (1, Number::Integer(value)) => Number::Decimal(BigDecimal::from(value.clone())),
+            (2, number) => Number::Float(number.to_f64() as f32),
+            (_, number) => Number::Double(number.to_f64()),
+        }
+    }
+
+    /// Converts both numbers to the type of the more general one
+    fn promote_pair(a: &Number, b: &Number) -> (Number, Number) {
+        let rank = a.rank().max(b.rank());
+        (a.promote(rank), b.promote(rank))
+    }
+
+    pub(crate) fn to_f64(&self) -> f64 {
+        match self {
+            Number::Integer(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Decimal(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Float(value) => f64::from(*value),
+            Number::Double(value) => *value,
+        }
+    }
+
+    /// Whether the number is an integer or decimal zero, which exact division is undefined for
+    fn is_exact_zero(&self) -> bool {
+        match self {
+            Number::Integer(value) => value.is_zero(),
+            Number::Decimal(value) => value.is_zero(),
+            _ => false,
+        }
+    }
+
+    /// Compares two numbers after promotion, returning `None` if either is NaN
+    pub(crate) fn compare(&self, other: &Number) -> Option<Ordering> {
+        match Number::promote_pair(self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(&b)),
+            (Number::Decimal(a), Number::Decimal(b)) => Some(a.cmp(&b)),
+            (Number::Float(a), Number::Float(b)) => a.partial_cmp(&b),
+            (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
+        }
+    }
+
+    fn add(&self, other: &Number) -> Number {
+        binary(self, other, |a, b| a + b, |a, b| a + b, |a, b| a + b, |a, b| a + b)
+    }
+
+    fn subtract(&self, other: &Number) -> Number {
+        binary(self, other, |a, b| a - b, |a, b| a - b, |a, b| a - b, |a, b| a - b)
+    }
+
+    fn multiply(&self, other: &Number) -> Number {
+        binary(self, other, |a, b| a * b, |a, b| a * b, |a, b| a * b, |a, b| a * b)
+    }
+
+    /// Divides as XPath does, so dividing two integers gives a decimal
+    ///
+    /// Returns `None` when an integer or decimal is divided by zero.
+    fn divide(&self, other: &Number) -> Option<Number> {
+        if other.is_exact_zero() && self.rank() < 2 {
+            return None;
+        }
+        let (a, b) = match Number::promote_pair(self, other) {
+            (Number::Integer(a), Number::Integer(b)) => (Number::Decimal(a.into()), Number::Decimal(b.into())),
+            pair => pair,
+        };
+        Some(match (a, b) {
+            (Number::Decimal(a), Number::Decimal(b)) => Number::Decimal((a / b).normalized()),
+            (Number::Float(a), Number::Float(b)) => Number::Float(a / b),
+            (a, b) => Number::Double(a.to_f64() / b.to_f64()),
+        })
+    }
+
+    /// The remainder of truncating division, `None` for a zero integer or decimal divisor
+    fn remainder(&self, other: &Number) -> Option<Number> {
+        if other.is_exact_zero() {
+            return None;
+        }
+        Some(match Number::promote_pair(self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Number::Integer(a % b),
+            (Number::Decimal(a), Number::Decimal(b)) => Number::Decimal(a % b),
+            (Number::Float(a), Number::Float(b)) => Number::Float(a % b),
+            (a, b) => Number::Double(a.to_f64() % b.to_f64()),
+        })
+    }
+
+    /// The quotient of dividing and truncating towards zero, as an integer
+    fn integer_quotient(&self, other: &Number) -> Option<Number> {
+        match Number::promote_pair(self, other) {
+            (Number::Integer(_), Number::Integer(b)) if b.is_zero() => None,
+            (Number::Integer(a), Number::Integer(b)) => Some(Number::Integer(a / b)),
+            (a, b) => a.divide(&b)?.truncate(),
+        }
+    }
+
+    /// Raises the number to a power
+    ///
+    /// An integer raised to a non-negative integer stays an exact integer,
+    /// unless the result would have more than [`MAX_POWER_BITS`] bits;
+    /// every other combination is computed in floating point.
+    fn power(&self, exponent: &Number) -> Number {
+        if let (Number::Integer(base), Number::Integer(exponent)) = (self, exponent) {
+            if let Some(exponent) = exponent.to_u32() {
+                let trivial = base.magnitude() <= &BigUint::from(1u8);
+                if trivial || base.bits().saturating_mul(u64::from(exponent)) <= MAX_POWER_BITS {
+                    return Number::Integer(base.pow(exponent));
+                }
+            }
+        }
+        match Number::promote_pair(self, exponent) {
+            (Number::Float(a), Number::Float(b)) => Number::Float(a.powf(b)),
+            (a, b) => Number::Double(a.to_f64().powf(b.to_f64())),
+        }
+    }
+
+    fn negate(&self) -> Number {
+        match self {
+            Number::Integer(value) => Number::Integer(-value),
+            Number::Decimal(value) => Number::Decimal(-value),
+            Number::Float(value) => Number::Float(-value),
+            Number::Double(value) => Number::Double(-value),
+        }
+    }
+
+    fn absolute(&self) -> Number {
+        match self {
+            Number::Integer(value) => Number::Integer(value.abs()),
+            Number::Decimal(value) => Number::Decimal(value.abs()),
+            Number::Float(value) => Number::Float(value.abs()),
+            Number::Double(value) => Number::Double(value.abs()),
+        }
+    }
+
+    /// Rounds to an integer, or returns `None` for NaN and infinities
+    fn round_with(&self, mode: RoundingMode, float: fn(f64) -> f64) -> Option<Number> {
+        let decimal = match self {
+            Number::Integer(value) => return Some(Number::Integer(value.clone())),
+            Number::Decimal(value) => value.with_scale_round(0, mode),
+            number => BigDecimal::from_f64(float(number.to_f64()))?,
+        };
+        let (digits, _) = decimal.with_scale(0).into_bigint_and_exponent();
+        Some(Number::Integer(digits))
+    }
+
+    fn floor(&self) -> Option<Number> {
+        self.round_with(RoundingMode::Floor, f64::floor)
+    }
+
+    fn ceiling(&self) -> Option<Number> {
+        self.round_with(RoundingMode::Ceiling, f64::ceil)
+    }
+
+    /// Rounds half away from zero
+    fn round(&self) -> Option<Number> {
+        self.round_with(RoundingMode::HalfUp, f64::round)
+    }
+
+    fn truncate(&self) -> Option<Number> {
+        self.round_with(RoundingMode::Down, f64::trunc)
+    }
+}
+
+/// Applies an operation to two numbers promoted to a common type
+fn binary(
+    a: &Number,
+    b: &Number,
+    integer: fn(BigInt, BigInt) -> BigInt,
+    decimal: fn(BigDecimal, BigDecimal) -> BigDecimal,
+    float: fn(f32, f32) -> f32,
+    double: fn(f64, f64) -> f64,
+) -> Number {
+    match Number::promote_pair(a, b) {
+        (Number::Integer(a), Number::Integer(b)) => Number::Integer(integer(a, b)),
+        (Number::Decimal(a), Number::Decimal(b)) => Number::Decimal(decimal(a, b)),
+        (Number::Float(a), Number::Float(b)) => Number::Float(float(a, b)),
+        (a, b) => Number::Double(double(a.to_f64(), b.to_f64())),
+    }
+}
+
+fn parse_integer(value: &str) -> Option<BigInt> {
+    BigInt::from_str(value.strip_prefix('+').unwrap_or(value)).ok()
+}
+
+/// Parses an `xsd:double` or `xsd:float`, including `INF`, `-INF` and `NaN`
+fn parse_float(value: &str) -> Option<f64> {
+    match value {
+        "INF" | "+INF" => Some(f64::INFINITY),
+        "-INF" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ if value.chars().any(|c| c.is_ascii_alphabetic() && c != 'e' && c != 'E') => None,
+        _ => value.parse().ok(),
+    }
+}
+
+/// The canonical `xsd:decimal` form, which always has a fractional part
+fn decimal_lexical(value: &BigDecimal) -> String {
+    let lexical = value.normalized().to_plain_string();
+    if lexical.contains('.') {
+        lexical
+    } else {
+        format!("{}.0", lexical)
+    }
+}
+
+/// The canonical `xsd:double` form, e.g. `1.5E2`
+fn double_lexical(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+    let lexical = format!("{:E}", value);
+    match lexical.split_once('E') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => format!("{}.0E{}", mantissa, exponent),
+        _ => lexical,
+    }
+}
+
+fn iri(local: &str) -> String {
+    format!("{}{}", vocab::MATH, local)
+}
+
+/// Reads a bound argument as a number
+///
+/// Returns `None` for terms that are not numbers, which makes the builtin
+/// fail rather than raise an error.
+fn number(builtin: &str, term: &Term) -> Result<Option<Number>, BuiltinError> {
+    if !term.is_ground() {
+        return Err(BuiltinError::insufficiently_bound(builtin));
+    }
+    Number::from_term(builtin, term)
+}
+
+/// Reads a list subject of numbers, returning `None` if any member is not a number
+fn numbers(builtin: &str, subject: &Term) -> Result<Option<Vec<Number>>, BuiltinError> {
+    let mut numbers = Vec::new();
+    for item in list_arguments(builtin, subject)? {
+        match number(builtin, item)? {
+            Some(number) => numbers.push(number),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(numbers))
+}
+
+/// Reads a list subject of exactly two numbers
+fn pair(builtin: &str, subject: &Term) -> Result<Option<(Number, Number)>, BuiltinError> {
+    let Some(numbers) = numbers(builtin, subject)? else {
+        return Ok(None);
+    };
+    match <[Number; 2]>::try_from(numbers) {
+        Ok([a, b]) => Ok(Some((a, b))),
+        Err(numbers) => Err(BuiltinError::invalid_argument(
+            builtin,
+            format!("expected a list of 2 numbers, got {}", numbers.len()),
+        )),
+    }
+}
+
+/// Binds or checks the object against a computed number
+///
+/// A bound numeric object holds when it is numerically equal to the value,
+/// so `(1 2) math:sum 3.0` holds although the sum is the integer `3`.
+fn number_result(builtin: &str, object: &Term, value: Option<Number>, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+    if object.is_ground() {
+        if let Some(expected) = Number::from_term(builtin, object)? {
+            return Ok(holds(expected.compare(&value) == Some(Ordering::Equal), binding));
+        }
+    }
+    Ok(unify_result(object, value.to_term(), binding))
+}
+
+/// Registers a function from a list of numbers to a number
+fn register_list_function(
+    registry: &mut BuiltinRegistry,
+    local: &str,
+    function: impl Fn(&str, Vec<Number>) -> Result<Option<Number>, BuiltinError> + Send + Sync + 'static,
+) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = match numbers(&name, subject)? {
+            Some(numbers) => function(&name, numbers)?,
+            None => None,
+        };
+        number_result(&name, object, value, binding)
+    });
+}
+
+/// Registers a function from a list of two numbers to a number
+fn register_pair_function(
+    registry: &mut BuiltinRegistry,
+    local: &str,
+    function: impl Fn(&Number, &Number) -> Option<Number> + Send + Sync + 'static,
+) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = pair(&name, subject)?.and_then(|(a, b)| function(&a, &b));
+        number_result(&name, object, value, binding)
+    });
+}
+
+/// Registers a function from a number to a number
+fn register_unary_function(
+    registry: &mut BuiltinRegistry,
+    local: &str,
+    function: impl Fn(&Number) -> Option<Number> + Send + Sync + 'static,
+) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = number(&name, subject)?.and_then(|value| function(&value));
+        number_result(&name, object, value, binding)
+    });
+}
+
+/// Registers a function computed in double precision
+fn register_double_function(registry: &mut BuiltinRegistry, local: &str, function: fn(f64) -> f64) {
+    register_unary_function(registry, local, move |value| Some(Number::Double(function(value.to_f64()))));
+}
+
+/// Lets a function already registered for numbers combine dates, times and durations too
+///
+/// The members of the subject list are combined from left to right. A list
+/// that is not all dates, times and durations is left to the numeric function.
+fn register_temporal_function(registry: &mut BuiltinRegistry, local: &str, function: fn(&Temporal, &Temporal) -> Option<Temporal>) {
+    let name = iri(local);
+    let numeric = registry.get(&name).cloned().expect("the numeric function is registered first");
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let mut values = Vec::new();
+        for item in list_arguments(&name, subject)? {
+            match item.is_ground().then(|| Temporal::from_term(&name, item)).transpose()?.flatten() {
+                Some(value) => values.push(value),
+                None => return numeric.evaluate(subject, object, binding),
+            }
+        }
+        let mut values = values.into_iter();
+        let Some(value) = values.next().and_then(|first| values.try_fold(first, |a, b| function(&a, &b))) else {
+            return numeric.evaluate(subject, object, binding);
+        };
+        if object.is_ground() {
+            if let Some(expected) = Temporal::from_term(&name, object)? {
+                return Ok(holds(expected.compare(&value) == Some(Ordering::Equal), binding));
+            }
+        }
+        Ok(unify_result(object, value.to_term(), binding))
+    });
+}
+
+/// Registers a comparison between two numbers, or two dates, times or durations
+fn register_comparison(registry: &mut BuiltinRegistry, local: &str, test: fn(Option<Ordering>) -> bool) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let order = match (number(&name, subject)?, number(&name, object)?) {
+            (Some(a), Some(b)) => a.compare(&b),
+            _ => match (Temporal::from_term(&name, subject)?, Temporal::from_term(&name, object)?) {
+                (Some(a), Some(b)) => a.compare(&b),
+                _ => return Ok(Vec::new()),
+            },
+        };
+        Ok(holds(test(order), binding))
+    });
+}
+
+/// Registers the builtins of the `math:` namespace
+///
+/// Functions take their arguments as the subject, a list for functions of
+/// several arguments, and give their result as the object. Arguments are
+/// promoted to a common XSD numeric type as in XPath, and integers have
+/// arbitrary precision. `math:sum`, `math:difference` and the comparisons
+/// also work on `xsd:dateTime`, `xsd:date` and `xsd:duration` literals.
+pub(crate) fn register(registry: &mut BuiltinRegistry) {
+    register_list_function(registry, "sum", |_, numbers| {
+        Ok(Some(numbers.iter().fold(Number::Integer(BigInt::zero()), |sum, n| sum.add(n))))
+    });
+    register_list_function(registry, "product", |_, numbers| {
+        Ok(Some(numbers.iter().fold(Number::Integer(BigInt::from(1)), |product, n| product.multiply(n))))
+    });
+    for (local, pick) in [("min", Ordering::Less), ("max", Ordering::Greater)] {
+        register_list_function(registry, local, move |name, numbers| {
+            let mut numbers = numbers.into_iter();
+            let first = numbers
+                .next()
+                .ok_or_else(|| BuiltinError::invalid_argument(name, "expected at least one number"))?;
+            Ok(Some(numbers.fold(first, |best, n| if n.compare(&best) == Some(pick) { n } else { best })))
+        });
+    }
+
+    register_pair_function(registry, "difference", |a, b| Some(a.subtract(b)));
+    register_temporal_function(registry, "sum", Temporal::add);
+    register_temporal_function(registry, "difference", Temporal::subtract);
+    register_pair_function(registry, "quotient", Number::divide);
+    register_pair_function(registry, "remainder", Number::remainder);
+    register_pair_function(registry, "integerQuotient", Number::integer_quotient);
+    register_pair_function(registry, "exponentiation", |a, b| Some(a.power(b)));
+    register_pair_function(registry, "logarithm", |a, b| Some(Number::Double(a.to_f64().log(b.to_f64()))));
+    register_pair_function(registry, "atan2", |a, b| Some(Number::Double(a.to_f64().atan2(b.to_f64()))));
+
+    register_unary_function(registry, "absoluteValue", |n| Some(n.absolute()));
+    register_unary_function(registry, "negation", |n| Some(n.negate()));
+    register_unary_function(registry, "floor", Number::floor);
+    register_unary_function(registry, "ceiling", Number::ceiling);
+    register_unary_function(registry, "rounded", Number::round);
+
+    register_double_function(registry, "sin", f64::sin);
+    register_double_function(registry, "cos", f64::cos);
+    register_double_function(registry, "tan", f64::tan);
+    register_double_function(registry, "asin", f64::asin);
+    register_double_function(registry, "acos", f64::acos);
+    register_double_function(registry, "atan", f64::atan);
+    register_double_function(registry, "sinh", f64::sinh);
+    register_double_function(registry, "cosh", f64::cosh);
+    register_double_function(registry, "tanh", f64::tanh);
+    register_double_function(registry, "degrees", f64::to_degrees);
+    register_double_function(registry, "radians", f64::to_radians);
+
+    register_comparison(registry, "greaterThan", |order| order == Some(Ordering::Greater));
+    register_comparison(registry, "lessThan", |order| order == Some(Ordering::Less));
+    register_comparison(registry, "notGreaterThan", |order| matches!(order, Some(Ordering::Less | Ordering::Equal)));
+    register_comparison(registry, "notLessThan", |order| matches!(order, Some(Ordering::Greater | Ordering::Equal)));
+    register_comparison(registry, "equalTo", |order| order == Some(Ordering::Equal));
+    register_comparison(registry, "notEqualTo", |order| order != Some(Ordering::Equal));
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use oxrdf::Literal;
+use regex::Regex;
+
+use crate::builtin::{holds, integer_term, list_arguments, unify_result, BuiltinRegistry};
+use crate::error::BuiltinError;
+use crate::model::Term;
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// The lexical form of a literal argument and its language tag, if any
+struct Text {
+    value: String,
+    language: Option<String>,
+}
+
+impl Text {
+    /// Whether `other` can be looked for in this string, as SPARQL's string functions require
+    ///
+    /// A language-tagged argument only goes with a string of the same
+    /// language; a plain argument goes with any string.
+    fn compatible_with(&self, other: &Text) -> bool {
+        other.language.is_none() || other.language == self.language
+    }
+
+    /// Builds a result string in the language of this one
+    fn derive(&self, value: String) -> Term {
+        string_term(value, self.language.as_deref())
+    }
+}
+
+fn string_term(value: String, language: Option<&str>) -> Term {
+    let literal = match language {
+        Some(language) => Literal::new_language_tagged_literal_unchecked(value, language),
+        None => Literal::new_simple_literal(value),
+    };
+    Term::Literal(literal)
+}
+
+fn iri(local: &str) -> String {
+    format!("{}{}", vocab::STRING, local)
+}
+
+/// Reads a bound argument as a string
+///
+/// Any literal is read by its lexical form, so numbers can be concatenated
+/// and searched like strings. Returns `None` for IRIs, blank nodes, lists
+/// and formulas, which makes the builtin fail.
+fn text(builtin: &str, term: &Term) -> Result<Option<Text>, BuiltinError> {
+    match term {
+        Term::Literal(literal) => Ok(Some(Text {
+            value: literal.value().to_string(),
+            language: literal.language().map(str::to_string),
+        })),
+        _ if !term.is_ground() => Err(BuiltinError::insufficiently_bound(builtin)),
+        _ => Ok(None),
+    }
+}
+
+/// Reads a list subject of strings, returning `None` if any member is not a literal
+fn texts(builtin: &str, subject: &Term) -> Result<Option<Vec<Text>>, BuiltinError> {
+    let mut texts = Vec::new();
+    for item in list_arguments(builtin, subject)? {
+        match text(builtin, item)? {
+            Some(text) => texts.push(text),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(texts))
+}
+
+/// Reads a list subject of exactly `N` strings
+fn text_arguments<const N: usize>(builtin: &str, subject: &Term) -> Result<Option<[Text; N]>, BuiltinError> {
+    let Some(texts) = texts(builtin, subject)? else {
+        return Ok(None);
+    };
+    <[Text; N]>::try_from(texts).map(Some).map_err(|texts| {
+        BuiltinError::invalid_argument(builtin, format!("expected a list of {} strings, got {}", N, texts.len()))
+    })
+}
+
+/// Unifies the object with a computed string, or fails if there is none
+fn text_result(object: &Term, value: Option<Term>, binding: &Substitution) -> Vec<Substitution> {
+    value.map(|value| unify_result(object, value, binding)).unwrap_or_default()
+}
+
+/// Compiled regular expressions, shared by the builtins that take one
+///
+/// Patterns are written for PCRE, as EYE uses them, and translated to the
+/// `regex` crate's syntax by [`translate_pattern`]. Patterns using what the
+/// crate does not support, such as backreferences and look-around, are
+/// invalid arguments.
+#[derive(Default)]
+struct Patterns {
+    compiled: Mutex<HashMap<String, Regex>>,
+}
+
+impl Patterns {
+    fn compile(&self, builtin: &str, pattern: &str) -> Result<Regex, BuiltinError> {
+        let mut compiled = self.compiled.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(regex) = compiled.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Regex::new(&translate_pattern(pattern))
+            .map_err(|error| BuiltinError::invalid_argument(builtin, error.to_string()))?;
+        compiled.insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+}
+
+/// Translates the PCRE shorthand classes `\d`, `\w` and `\s` and their negations to ASCII classes
+///
+/// PCRE only matches ASCII with them by default, while the `regex` crate
+/// matches any Unicode digit, word character or space.
+fn translate_pattern(pattern: &str) -> String {
+    let mut translated = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let Some(escaped) = chars.next() else {
+                    translated.push(c);
+                    break;
+                };
+                let class = match escaped.to_ascii_lowercase() {
+                    'd' => Some("digit"),
+                    'w' => Some("word"),
+                    's' => Some("space"),
+                    _ => None,
+                };
+                match (class, escaped.is_ascii_uppercase(), in_class) {
+                    (Some(class), false, false) => translated.push_str(&format!("[[:{}:]]", class)),
+                    (Some(class), true, false) => translated.push_str(&format!("[[:^{}:]]", class)),
+                    (Some(class), false, true) => translated.push_str(&format!("[:{}:]", class)),
+                    (Some(class), true, true) => translated.push_str(&format!("[:^{}:]", class)),
+                    (None, _, _) => {
+                        translated.push(c);
+                        translated.push(escaped);
+                    }
+                }
+            }
+            '[' if !in_class => {
+                in_class = true;
+                translated.push(c);
+                // A `]` right after the opening bracket or its negation is a literal
+                if chars.peek() == Some(&'^') {
+                    translated.push(chars.next().unwrap_or('^'));
+                }
+                if chars.peek() == Some(&']') {
+                    translated.push_str("\\]");
+                    chars.next();
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                translated.push(c);
+            }
+            _ => translated.push(c),
+        }
+    }
+    translated
+}
+
+/// Translates PCRE's `\1` group references in a replacement to the `regex` crate's `${1}`
+///
+/// Any other `$`, escaped as `\$` or not, is a literal dollar sign, which
+/// the `regex` crate writes `$$`.
+fn translate_replacement(replacement: &str) -> String {
+    let mut translated = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            translated.push_str("$$");
+            continue;
+        }
+        if c != '\\' {
+            translated.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some(digit) if digit.is_ascii_digit() => {
+                let mut group = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    group.push(digit);
+                }
+                translated.push_str(&format!("${{{}}}", group));
+            }
+            Some('\\') => {
+                chars.next();
+                translated.push('\\');
+            }
+            Some('$') => {
+                chars.next();
+                translated.push_str("$$");
+            }
+            _ => translated.push(c),
+        }
+    }
+    translated
+}
+
+/// Formats a string like cwm's `string:format`, substituting `%s` with each argument in turn
+fn format(builtin: &str, template: &str, arguments: &[Text]) -> Result<String, BuiltinError> {
+    let mut formatted = String::with_capacity(template.len());
+    let mut arguments = arguments.iter();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => formatted.push('%'),
+            Some('s') => {
+                let argument = arguments
+                    .next()
+                    .ok_or_else(|| BuiltinError::invalid_argument(builtin, "not enough arguments for the format"))?;
+                formatted.push_str(&argument.value);
+            }
+            other => {
+                let directive = other.map(String::from).unwrap_or_default();
+                return Err(BuiltinError::invalid_argument(builtin, format!("unsupported directive '%{}'", directive)));
+            }
+        }
+    }
+    Ok(formatted)
+}
+
+/// Registers a test between the subject and object strings
+fn register_test(registry: &mut BuiltinRegistry, local: &str, test: fn(&Text, &Text) -> bool) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let (Some(a), Some(b)) = (text(&name, subject)?, text(&name, object)?) else {
+            return Ok(Vec::new());
+        };
+        Ok(holds(test(&a, &b), binding))
+    });
+}
+
+/// Registers a function from the subject string to the object
+fn register_function(registry: &mut BuiltinRegistry, local: &str, function: fn(&Text) -> Term) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = text(&name, subject)?.map(|text| function(&text));
+        Ok(text_result(object, value, binding))
+    });
+}
+
+/// Registers the builtins of the `string:` namespace
+///
+/// Arguments are literals, read by their lexical form. Results that are
+/// derived from a language-tagged string keep its language, as in SPARQL,
+/// and are plain strings otherwise. Regular expressions follow EYE's PCRE
+/// patterns as far as the `regex` crate can.
+pub(crate) fn register(registry: &mut BuiltinRegistry) {
+    let name = iri("concatenation");
+    registry.register(&name.clone(), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = texts(&name, subject)?.map(|texts| {
+            let value: String = texts.iter().map(|text| text.value.as_str()).collect();
+            let language = texts.first().and_then(|first| first.language.as_deref());
+            let shared = texts.iter().all(|text| text.language.as_deref() == language);
+            string_term(value, if shared { language } else { None })
+        });
+        Ok(text_result(object, value, binding))
+    });
+
+    let name = iri("format");
+    registry.register(&name.clone(), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let Some(texts) = texts(&name, subject)? else {
+            return Ok(Vec::new());
+        };
+        let Some((template, arguments)) = texts.split_first() else {
+            return Err(BuiltinError::invalid_argument(&name, "expected a format string"));
+        };
+        let value = string_term(format(&name, &template.value, arguments)?, None);
+        Ok(unify_result(object, value, binding))
+    });
+
+    register_test(registry, "contains", |a, b| a.compatible_with(b) && a.value.contains(&b.value));
+    register_test(registry, "containsIgnoringCase", |a, b| {
+        a.compatible_with(b) && a.value.to_lowercase().contains(&b.value.to_lowercase())
+    });
+    register_test(registry, "startsWith", |a, b| a.compatible_with(b) && a.value.starts_with(&b.value));
+    register_test(registry, "endsWith", |a, b| a.compatible_with(b) && a.value.ends_with(&b.value));
+    register_test(registry, "equalIgnoringCase", |a, b| a.value.to_lowercase() == b.value.to_lowercase());
+    register_test(registry, "notEqualIgnoringCase", |a, b| a.value.to_lowercase() != b.value.to_lowercase());
+    register_test(registry, "greaterThan", |a, b| a.value > b.value);
+    register_test(registry, "lessThan", |a, b| a.value < b.value);
+    register_test(registry, "notGreaterThan", |a, b| a.value <= b.value);
+    register_test(registry, "notLessThan", |a, b| a.value >= b.value);
+
+    register_function(registry, "length", |text| integer_term(text.value.chars().count()));
+    register_function(registry, "lowerCase", |text| text.derive(text.value.to_lowercase()));
+    register_function(registry, "upperCase", |text| text.derive(text.value.to_uppercase()));
+
+    let patterns = Arc::new(Patterns::default());
+    for (local, expected) in [("matches", true), ("notMatches", false)] {
+        let name = iri(local);
+        let patterns = Arc::clone(&patterns);
+        registry.register(&name.clone(), move |subject: &Term, object: &Term, binding: &Substitution| {
+            let (Some(text), Some(pattern)) = (text(&name, subject)?, text(&name, object)?) else {
+                return Ok(Vec::new());
+            };
+            let regex = patterns.compile(&name, &pattern.value)?;
+            Ok(holds(regex.is_match(&text.value) == expected, binding))
+        });
+    }
+
+    let name = iri("replace");
+    let replace_patterns = Arc::clone(&patterns);
+    registry.register(&name.clone(), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let Some([text, pattern, replacement]) = text_arguments(&name, subject)? else {
+            return Ok(Vec::new());
+        };
+        let regex = replace_patterns.compile(&name, &pattern.value)?;
+        let replaced = regex.replace_all(&text.value, translate_replacement(&replacement.value).as_str());
+        Ok(unify_result(object, text.derive(replaced.into_owned()), binding))
+    });
+
+    let name = iri("scrape");
+    registry.register(&name.clone(), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let Some([text, pattern]) = text_arguments(&name, subject)? else {
+            return Ok(Vec::new());
+        };
+        let regex = patterns.compile(&name, &pattern.value)?;
+        let scraped = regex
+            .captures(&text.value)
+            .and_then(|captures| captures.get(1))
+            .map(|group| text.derive(group.as_str().to_string()));
+        Ok(text_result(object, scraped, binding))
+    });
+}
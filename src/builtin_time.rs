@@ -0,0 +1,519 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Write};
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Datelike, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use oxrdf::{Literal, NamedNode};
+
+use crate::builtin::{unify_result, Builtin, BuiltinContext, BuiltinRegistry};
+use crate::builtin_math::Number;
+use crate::error::BuiltinError;
+use crate::model::Term;
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// Duration datatypes, which are all read as `xsd:duration`s
+const DURATION_TYPES: &[&str] = &["duration", "dayTimeDuration", "yearMonthDuration"];
+
+fn iri(local: &str) -> String {
+    format!("{}{}", vocab::TIME, local)
+}
+
+/// A source of the current time for `time:localTime`
+///
+/// Engines use the [`SystemClock`]; a [`FixedClock`] makes results that
+/// depend on the time reproducible.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// The current time, with the offset of the local time zone
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// The clock of the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+}
+
+/// A clock that is stopped at a given time
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    time: DateTime<FixedOffset>,
+}
+
+impl FixedClock {
+    /// Creates a clock that always reads `time`
+    pub fn new(time: DateTime<FixedOffset>) -> Self {
+        FixedClock { time }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.time
+    }
+}
+
+/// An `xsd:duration`, split into months and an exact amount of time as XPath does
+///
+/// Both parts have the same sign; a day is always 24 hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Duration {
+    months: i64,
+    time: TimeDelta,
+}
+
+impl Duration {
+    /// Builds a duration, returning `None` if its parts have different signs
+    fn new(months: i64, time: TimeDelta) -> Option<Duration> {
+        let mixed = (months < 0 && time > TimeDelta::zero()) || (months > 0 && time < TimeDelta::zero());
+        (!mixed).then_some(Duration { months, time })
+    }
+
+    /// Reads the lexical form of an `xsd:duration`, such as `-P1Y2M3DT4H5M6.5S`
+    fn parse(value: &str) -> Option<Duration> {
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let value = value.strip_prefix('P')?;
+        let (date, time) = match value.split_once('T') {
+            Some((_, "")) => return None,
+            Some((date, time)) => (date, time),
+            None => (value, ""),
+        };
+        if date.is_empty() && time.is_empty() {
+            return None;
+        }
+
+        let mut months: i64 = 0;
+        let mut seconds: i64 = 0;
+        let mut nanoseconds: u32 = 0;
+        for (designator, number) in components(date, "YMD")? {
+            let number: i64 = digits(number)?;
+            match designator {
+                'Y' => months = months.checked_add(number.checked_mul(12)?)?,
+                'M' => months = months.checked_add(number)?,
+                _ => seconds = seconds.checked_add(number.checked_mul(86_400)?)?,
+            }
+        }
+        for (designator, number) in components(time, "HMS")? {
+            if designator == 'S' {
+                let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+                seconds = seconds.checked_add(digits(whole)?)?;
+                if !fraction.is_empty() {
+                    // Checked before truncating, which is only safe on ASCII, to nanoseconds
+                    if !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+                        return None;
+                    }
+                    let fraction = format!("{:0<9}", &fraction[..fraction.len().min(9)]);
+                    nanoseconds = digits(&fraction)?.to_u32()?;
+                }
+                continue;
+            }
+            let unit = if designator == 'H' { 3_600 } else { 60 };
+            seconds = seconds.checked_add(digits(number)?.checked_mul(unit)?)?;
+        }
+
+        let time = TimeDelta::new(seconds, nanoseconds)?;
+        if negative {
+            Some(Duration { months: -months, time: -time })
+        } else {
+            Some(Duration { months, time })
+        }
+    }
+
+    /// Writes the duration in canonical form, e.g. `P1Y2M3DT4H5M6.5S`
+    fn lexical(&self) -> String {
+        let negative = self.months < 0 || self.time < TimeDelta::zero();
+        let months = self.months.unsigned_abs();
+        let time = self.time.abs();
+        let seconds = time.num_seconds();
+        let nanoseconds = time.subsec_nanos().unsigned_abs();
+
+        let mut lexical = String::from(if negative { "-P" } else { "P" });
+        for (value, designator) in [(months / 12, 'Y'), (months % 12, 'M'), (seconds.unsigned_abs() / 86_400, 'D')] {
+            if value > 0 {
+                let _ = write!(lexical, "{}{}", value, designator);
+            }
+        }
+        let (hours, minutes, seconds) = (seconds % 86_400 / 3_600, seconds % 3_600 / 60, seconds % 60);
+        if hours > 0 || minutes > 0 || seconds > 0 || nanoseconds > 0 {
+            lexical.push('T');
+            for (value, designator) in [(hours, 'H'), (minutes, 'M')] {
+                if value > 0 {
+                    let _ = write!(lexical, "{}{}", value, designator);
+                }
+            }
+            if seconds > 0 || nanoseconds > 0 {
+                let _ = write!(lexical, "{}{}S", seconds, fraction(nanoseconds));
+            }
+        }
+        if lexical.ends_with('P') {
+            return "PT0S".to_string();
+        }
+        lexical
+    }
+
+    fn negate(&self) -> Duration {
+        Duration { months: -self.months, time: -self.time }
+    }
+
+    fn add(&self, other: &Duration) -> Option<Duration> {
+        Duration::new(self.months.checked_add(other.months)?, self.time.checked_add(&other.time)?)
+    }
+
+    /// Orders durations whose parts are ordered the same way, such as `P1M` and `P1M1D`
+    fn compare(&self, other: &Duration) -> Option<Ordering> {
+        match (self.months.cmp(&other.months), self.time.cmp(&other.time)) {
+            (Ordering::Equal, order) | (order, Ordering::Equal) => Some(order),
+            (a, b) if a == b => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Moves a date and time by the duration, months first
+    fn shift(&self, value: NaiveDateTime) -> Option<NaiveDateTime> {
+        let months = Months::new(self.months.unsigned_abs().to_u32()?);
+        let shifted = if self.months < 0 {
+            value.checked_sub_months(months)?
+        } else {
+            value.checked_add_months(months)?
+        };
+        shifted.checked_add_signed(self.time)
+    }
+}
+
+/// Splits the date or time part of a duration into numbers and their designators, which must come in order
+fn components<'a>(mut part: &'a str, designators: &str) -> Option<Vec<(char, &'a str)>> {
+    let mut allowed = designators;
+    let mut found = Vec::new();
+    while !part.is_empty() {
+        let end = part.find(|c: char| c.is_ascii_alphabetic())?;
+        let designator = part[end..].chars().next()?;
+        allowed = &allowed[allowed.find(designator)? + 1..];
+        if end == 0 {
+            return None;
+        }
+        found.push((designator, &part[..end]));
+        part = &part[end + 1..];
+    }
+    Some(found)
+}
+
+/// Reads a string of ASCII digits
+fn digits(value: &str) -> Option<i64> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// The fractional seconds of a lexical form, e.g. `.5`, or nothing for whole seconds
+fn fraction(nanoseconds: u32) -> String {
+    if nanoseconds == 0 {
+        return String::new();
+    }
+    format!(".{:09}", nanoseconds).trim_end_matches('0').to_string()
+}
+
+/// Splits the time zone off a date or time, which is `Z` or an offset such as `-05:00`
+fn split_zone(value: &str) -> Option<(&str, Option<FixedOffset>)> {
+    if let Some(value) = value.strip_suffix('Z') {
+        return Some((value, Some(FixedOffset::east_opt(0)?)));
+    }
+    let bytes = value.as_bytes();
+    let n = bytes.len();
+    if n > 6 && matches!(bytes[n - 6], b'+' | b'-') && bytes[n - 3] == b':' {
+        let (value, zone) = value.split_at(n - 6);
+        let (hours, minutes) = (digits(&zone[1..3])?, digits(&zone[4..])?);
+        if hours > 14 || minutes > 59 {
+            return None;
+        }
+        let seconds = (hours * 3_600 + minutes * 60).to_i32()?;
+        let offset = if zone.starts_with('-') { FixedOffset::west_opt(seconds)? } else { FixedOffset::east_opt(seconds)? };
+        return Some((value, Some(offset)));
+    }
+    Some((value, None))
+}
+
+/// Writes a time zone as it appears in a lexical form
+fn zone(offset: Option<FixedOffset>) -> String {
+    let Some(offset) = offset else {
+        return String::new();
+    };
+    let seconds = offset.local_minus_utc();
+    if seconds == 0 {
+        return "Z".to_string();
+    }
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("{}{:02}:{:02}", sign, seconds.abs() / 3_600, seconds.abs() % 3_600 / 60)
+}
+
+/// A value of `xsd:dateTime`, `xsd:date` or `xsd:duration`
+///
+/// Dates and times keep the time zone they were written with, if any.
+/// Those without one are taken to be in UTC when compared with others.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Temporal {
+    DateTime(NaiveDateTime, Option<FixedOffset>),
+    Date(NaiveDate, Option<FixedOffset>),
+    Duration(Duration),
+}
+
+impl Temporal {
+    /// Reads a date, time or duration literal, returning `None` for other terms
+    ///
+    /// Plain strings holding one are accepted too, as cwm does. A literal
+    /// with one of these datatypes but an invalid lexical form is an error.
+    pub(crate) fn from_term(builtin: &str, term: &Term) -> Result<Option<Temporal>, BuiltinError> {
+        let Term::Literal(literal) = term else {
+            return Ok(None);
+        };
+        let value = literal.value().trim();
+        let datatype = literal.datatype().as_str();
+        let Some(local) = datatype.strip_prefix("http://www.w3.org/2001/XMLSchema#") else {
+            return Ok(None);
+        };
+        let parsed = match local {
+            "dateTime" | "dateTimeStamp" => Temporal::parse_date_time(value),
+            "date" => Temporal::parse_date(value),
+            "string" if literal.language().is_none() => {
+                let parsed = Temporal::parse_date_time(value)
+                    .or_else(|| Temporal::parse_date(value))
+                    .or_else(|| Duration::parse(value).map(Temporal::Duration));
+                return Ok(parsed);
+            }
+            _ if DURATION_TYPES.contains(&local) => Duration::parse(value).map(Temporal::Duration),
+            _ => return Ok(None),
+        };
+        parsed
+            .map(Some)
+            .ok_or_else(|| BuiltinError::invalid_argument(builtin, format!("'{}' is not a valid {}", value, datatype)))
+    }
+
+    fn parse_date_time(value: &str) -> Option<Temporal> {
+        let (value, offset) = split_zone(value)?;
+        let value = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        Some(Temporal::DateTime(value, offset))
+    }
+
+    fn parse_date(value: &str) -> Option<Temporal> {
+        let (value, offset) = split_zone(value)?;
+        let value = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+        Some(Temporal::Date(value, offset))
+    }
+
+    /// Writes the value as a literal of its type in canonical form
+    pub(crate) fn to_term(self) -> Term {
+        let (lexical, datatype) = match self {
+            Temporal::DateTime(value, offset) => {
+                let lexical = format!("{}{}{}", value.format("%Y-%m-%dT%H:%M:%S"), fraction(value.nanosecond()), zone(offset));
+                (lexical, vocab::XSD_DATE_TIME)
+            }
+            Temporal::Date(value, offset) => (format!("{}{}", value.format("%Y-%m-%d"), zone(offset)), vocab::XSD_DATE),
+            Temporal::Duration(duration) => (duration.lexical(), vocab::XSD_DURATION),
+        };
+        Term::Literal(Literal::new_typed_literal(lexical, NamedNode::new_unchecked(datatype)))
+    }
+
+    /// The moment a date or time stands for in UTC, a date standing for its start
+    fn instant(&self) -> Option<NaiveDateTime> {
+        let (value, offset) = match self {
+            Temporal::DateTime(value, offset) => (*value, offset),
+            Temporal::Date(value, offset) => (value.and_time(Default::default()), offset),
+            Temporal::Duration(_) => return None,
+        };
+        let seconds = offset.map(|offset| offset.local_minus_utc()).unwrap_or_default();
+        value.checked_sub_signed(TimeDelta::seconds(i64::from(seconds)))
+    }
+
+    /// Orders two dates or times by the moment they stand for, or two durations
+    pub(crate) fn compare(&self, other: &Temporal) -> Option<Ordering> {
+        match (self, other) {
+            (Temporal::Duration(a), Temporal::Duration(b)) => a.compare(b),
+            (a, b) => Some(a.instant()?.cmp(&b.instant()?)),
+        }
+    }
+
+    /// Moves a date or time by a duration, keeping its time zone
+    fn shift(&self, duration: &Duration) -> Option<Temporal> {
+        match self {
+            Temporal::DateTime(value, offset) => Some(Temporal::DateTime(duration.shift(*value)?, *offset)),
+            Temporal::Date(value, offset) => {
+                let shifted = duration.shift(value.and_time(Default::default()))?;
+                Some(Temporal::Date(shifted.date(), *offset))
+            }
+            Temporal::Duration(_) => None,
+        }
+    }
+
+    /// Adds a duration to a date, time or duration
+    pub(crate) fn add(&self, other: &Temporal) -> Option<Temporal> {
+        match (self, other) {
+            (Temporal::Duration(a), Temporal::Duration(b)) => a.add(b).map(Temporal::Duration),
+            (Temporal::Duration(duration), value) | (value, Temporal::Duration(duration)) => value.shift(duration),
+            _ => None,
+        }
+    }
+
+    /// Subtracts a duration from a date, time or duration, or gives the duration between two dates or times
+    pub(crate) fn subtract(&self, other: &Temporal) -> Option<Temporal> {
+        match (self, other) {
+            (Temporal::Duration(a), Temporal::Duration(b)) => a.add(&b.negate()).map(Temporal::Duration),
+            (value, Temporal::Duration(duration)) => value.shift(&duration.negate()),
+            (Temporal::Duration(_), _) => None,
+            (a, b) => {
+                let time = a.instant()?.signed_duration_since(b.instant()?);
+                Some(Temporal::Duration(Duration { months: 0, time }))
+            }
+        }
+    }
+}
+
+/// Reads a bound argument as a date, time or duration
+fn temporal(builtin: &str, term: &Term) -> Result<Option<Temporal>, BuiltinError> {
+    if !term.is_ground() {
+        return Err(BuiltinError::insufficiently_bound(builtin));
+    }
+    Temporal::from_term(builtin, term)
+}
+
+/// An `xsd:integer` literal of any sign
+fn integer(value: impl Into<BigInt>) -> Term {
+    Number::Integer(value.into()).to_term()
+}
+
+/// Seconds with a fraction, which are an `xsd:integer` when whole and an `xsd:decimal` otherwise
+fn seconds(seconds: i64, nanoseconds: u32) -> Term {
+    if nanoseconds == 0 {
+        return integer(seconds);
+    }
+    let nanoseconds = BigInt::from(seconds) * 1_000_000_000 + nanoseconds;
+    Number::Decimal(BigDecimal::new(nanoseconds, 9)).to_term()
+}
+
+/// Registers a builtin giving one component of a date or time
+fn register_component(registry: &mut BuiltinRegistry, local: &str, component: fn(&Temporal) -> Option<Term>) {
+    let name = iri(local);
+    registry.register(&iri(local), move |subject: &Term, object: &Term, binding: &Substitution| {
+        let value = temporal(&name, subject)?.as_ref().and_then(component);
+        Ok(value.map(|value| unify_result(object, value, binding)).unwrap_or_default())
+    });
+}
+
+/// `"" time:localTime ?t` gives the current time as an `xsd:dateTime`
+///
+/// In an engine this is the time its clock read when the current call of
+/// `saturate` or `prove` started, so all conclusions of a run agree on the
+/// time; evaluated on its own it reads the system clock. Proof steps record
+/// it in their evidence like any other builtin statement.
+struct LocalTime;
+
+impl Builtin for LocalTime {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        self.evaluate_in(&BuiltinContext::new(&BuiltinRegistry::new()), subject, object, binding)
+    }
+
+    fn evaluate_in(
+        &self,
+        context: &BuiltinContext<'_>,
+        _subject: &Term,
+        object: &Term,
+        binding: &Substitution,
+    ) -> Result<Vec<Substitution>, BuiltinError> {
+        let now = context.local_time().unwrap_or_else(|| SystemClock.now());
+        let value = Temporal::DateTime(now.naive_local(), Some(*now.offset()));
+        Ok(unify_result(object, value.to_term(), binding))
+    }
+}
+
+/// Splits a number of seconds into whole seconds and nanoseconds
+///
+/// Decimals are truncated to nanoseconds, while floats and doubles are
+/// rounded, carrying a full second into the whole seconds.
+fn split_seconds(number: &Number) -> Option<(i64, u32)> {
+    let magnitude = number.to_f64().abs();
+    if magnitude.is_nan() || magnitude >= i64::MAX as f64 {
+        return None;
+    }
+    let nanoseconds: i128 = match number {
+        Number::Integer(value) => value.to_i128()? * 1_000_000_000,
+        Number::Decimal(value) => (value * BigDecimal::from(1_000_000_000)).with_scale(0).as_bigint_and_exponent().0.to_i128()?,
+        _ => (number.to_f64() * 1e9).round() as i128,
+    };
+    let whole = i64::try_from(nanoseconds.div_euclid(1_000_000_000)).ok()?;
+    Some((whole, nanoseconds.rem_euclid(1_000_000_000) as u32))
+}
+
+/// Registers the builtins of the `time:` namespace
+///
+/// These take an `xsd:dateTime` or `xsd:date` subject and give one of its
+/// components as the object. `time:localTime` reads the system clock.
+/// Comparing and subtracting dates, times and durations is done by the
+/// `math:` comparisons, `math:sum` and `math:difference`.
+pub(crate) fn register(registry: &mut BuiltinRegistry) {
+    register_component(registry, "year", |value| match value {
+        Temporal::DateTime(value, _) => Some(integer(value.year())),
+        Temporal::Date(value, _) => Some(integer(value.year())),
+        Temporal::Duration(_) => None,
+    });
+    register_component(registry, "month", |value| match value {
+        Temporal::DateTime(value, _) => Some(integer(value.month())),
+        Temporal::Date(value, _) => Some(integer(value.month())),
+        Temporal::Duration(_) => None,
+    });
+    register_component(registry, "day", |value| match value {
+        Temporal::DateTime(value, _) => Some(integer(value.day())),
+        Temporal::Date(value, _) => Some(integer(value.day())),
+        Temporal::Duration(_) => None,
+    });
+    register_component(registry, "hour", |value| match value {
+        Temporal::DateTime(value, _) => Some(integer(value.hour())),
+        _ => None,
+    });
+    register_component(registry, "minute", |value| match value {
+        Temporal::DateTime(value, _) => Some(integer(value.minute())),
+        _ => None,
+    });
+    register_component(registry, "second", |value| match value {
+        Temporal::DateTime(value, _) => Some(seconds(i64::from(value.second()), value.nanosecond())),
+        _ => None,
+    });
+    register_component(registry, "timeZone", |value| match value {
+        Temporal::DateTime(_, Some(offset)) | Temporal::Date(_, Some(offset)) => {
+            Some(Term::Literal(Literal::new_simple_literal(zone(Some(*offset)))))
+        }
+        _ => None,
+    });
+
+    let name = iri("inSeconds");
+    registry.register(&iri("inSeconds"), move |subject: &Term, object: &Term, binding: &Substitution| {
+        if subject.is_ground() {
+            let Some(instant) = temporal(&name, subject)?.and_then(|value| value.instant()) else {
+                return Ok(Vec::new());
+            };
+            let instant = instant.and_utc();
+            return Ok(unify_result(object, seconds(instant.timestamp(), instant.timestamp_subsec_nanos()), binding));
+        }
+        if !object.is_ground() {
+            return Err(BuiltinError::insufficiently_bound(&name));
+        }
+        let Some(number) = Number::from_term(&name, object)? else {
+            return Ok(Vec::new());
+        };
+        let Some(instant) = split_seconds(&number).and_then(|(whole, nanoseconds)| DateTime::from_timestamp(whole, nanoseconds))
+        else {
+            return Ok(Vec::new());
+        };
+        let value = Temporal::DateTime(instant.naive_utc(), FixedOffset::east_opt(0));
+        Ok(unify_result(subject, value.to_term(), binding))
+    });
+
+    registry.register(&iri("localTime"), LocalTime);
+}
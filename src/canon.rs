@@ -0,0 +1,556 @@
+// Canonical labelling and isomorphism checking for formulas
+//
+// Blank nodes and variables are the local names of a formula: renaming them
+// consistently does not change what it says. Following RDFC-1.0, every local
+// node is hashed from the statements it occurs in, and the hashes are refined
+// until they stop separating more nodes. Nodes that are still tied (symmetric
+// graphs) are distinguished by trying each of them in turn and keeping the
+// smallest resulting serialization, so the labelling is canonical rather than
+// a best effort.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use oxrdf::BlankNode;
+use sha2::{Digest, Sha256};
+
+use crate::model::{Formula, Statement, Term};
+
+/// Prefix of the canonical labels given to blank nodes and variables
+const LABEL_PREFIX: &str = "c14n";
+
+impl Formula {
+    /// Returns a copy of this formula with blank nodes and variables renamed to canonical labels
+    ///
+    /// Blank nodes become `_:c14n0`, `_:c14n1`, ... and variables `?c14n0`,
+    /// `?c14n1`, ..., including in nested formulas and quantifier lists. Two
+    /// formulas that are equal up to renaming canonicalize to equal formulas.
+    pub fn canonicalize(&self) -> Formula {
+        let canonicalizer = Canonicalizer::new(self);
+        let (_, labels) = canonicalizer.canonical_labels();
+        canonicalizer.relabel(self, &labels)
+    }
+
+    /// Serializes this formula with sorted statements and canonical labels
+    ///
+    /// Formulas that are equal up to a renaming of blank nodes and variables
+    /// have the same canonical form, so it can be compared or hashed to
+    /// deduplicate them.
+    pub fn canonical_form(&self) -> String {
+        Canonicalizer::new(self).canonical_labels().0
+    }
+
+    /// Checks whether two formulas are equal up to a renaming of blank nodes and variables
+    pub fn is_isomorphic(&self, other: &Formula) -> bool {
+        if self.statements.len() != other.statements.len()
+            || self.universal_vars.len() != other.universal_vars.len()
+            || self.existential_vars.len() != other.existential_vars.len()
+        {
+            return false;
+        }
+
+        self.canonical_form() == other.canonical_form()
+    }
+}
+
+/// The best labelling found so far by the search
+struct Leaf {
+    /// Canonical serialization under this labelling
+    form: String,
+
+    /// Local nodes in label order
+    order: Vec<usize>,
+}
+
+/// Bookkeeping for the search over ways of breaking ties between nodes
+struct SearchState {
+    /// Nodes individualized on the way to the current search node
+    path: Vec<usize>,
+
+    /// The first leaf reached, with the path that led to it
+    first: Option<(Leaf, Vec<usize>)>,
+
+    /// The leaf with the smallest serialization so far
+    best: Option<Leaf>,
+
+    /// Automorphisms discovered from leaves with equal serializations
+    automorphisms: Vec<Vec<usize>>,
+}
+
+/// Computes canonical labels for the blank nodes and variables of one formula
+struct Canonicalizer<'a> {
+    /// The formula being canonicalized
+    root: &'a Formula,
+
+    /// Distinct statements of the formula and of every nested formula
+    statements: Vec<&'a Statement>,
+
+    /// Index of every blank node label
+    blanks: HashMap<&'a str, usize>,
+
+    /// Index of every variable name
+    variables: HashMap<&'a str, usize>,
+
+    /// Whether each local node is a variable rather than a blank node
+    is_variable: Vec<bool>,
+
+    /// Statements that mention each local node, at any depth
+    occurrences: Vec<Vec<usize>>,
+
+    /// Top-level quantifiers that bind each local node
+    quantifiers: Vec<Vec<&'static str>>,
+}
+
+impl<'a> Canonicalizer<'a> {
+    fn new(formula: &'a Formula) -> Self {
+        let mut canonicalizer = Canonicalizer {
+            root: formula,
+            statements: Vec::new(),
+            blanks: HashMap::new(),
+            variables: HashMap::new(),
+            is_variable: Vec::new(),
+            occurrences: Vec::new(),
+            quantifiers: Vec::new(),
+        };
+
+        for var in sorted(&formula.universal_vars) {
+            let node = canonicalizer.register(var, true);
+            canonicalizer.quantifiers[node].push("@forAll");
+        }
+        for var in sorted(&formula.existential_vars) {
+            let node = canonicalizer.register(var, true);
+            canonicalizer.quantifiers[node].push("@forSome");
+        }
+        canonicalizer.collect_formula(formula);
+
+        canonicalizer
+    }
+
+    /// Returns the index of a local node, allocating one on first sight
+    fn register(&mut self, name: &'a str, variable: bool) -> usize {
+        let next = self.is_variable.len();
+        let names = if variable { &mut self.variables } else { &mut self.blanks };
+        let node = *names.entry(name).or_insert(next);
+        if node == next {
+            self.is_variable.push(variable);
+            self.occurrences.push(Vec::new());
+            self.quantifiers.push(Vec::new());
+        }
+        node
+    }
+
+    /// Records the statements of a formula and of the formulas nested in it
+    fn collect_formula(&mut self, formula: &'a Formula) {
+        for var in sorted(&formula.universal_vars).chain(sorted(&formula.existential_vars)) {
+            self.register(var, true);
+        }
+
+        for statement in &formula.statements {
+            let index = self.statements.len();
+            self.statements.push(statement);
+
+            let mut mentioned = Vec::new();
+            let mut nested = Vec::new();
+            for term in [&statement.subject, &statement.predicate, &statement.object] {
+                self.collect_term(term, &mut mentioned);
+                nested_formulas(term, &mut nested);
+            }
+            mentioned.sort_unstable();
+            mentioned.dedup();
+            for node in mentioned {
+                self.occurrences[node].push(index);
+            }
+
+            for formula in nested {
+                self.collect_formula(formula);
+            }
+        }
+    }
+
+    /// Collects every local node a term mentions, looking inside lists and nested formulas
+    fn collect_term(&mut self, term: &'a Term, mentioned: &mut Vec<usize>) {
+        match term {
+            Term::BlankNode(node) => mentioned.push(self.register(node.as_str(), false)),
+            Term::Variable(var) => mentioned.push(self.register(var, true)),
+            Term::List(items) => {
+                for item in items {
+                    self.collect_term(item, mentioned);
+                }
+            }
+            Term::Formula(formula) => {
+                for var in formula.universal_vars.iter().chain(&formula.existential_vars) {
+                    mentioned.push(self.register(var, true));
+                }
+                for statement in &formula.statements {
+                    for term in [&statement.subject, &statement.predicate, &statement.object] {
+                        self.collect_term(term, mentioned);
+                    }
+                }
+            }
+            Term::Iri(_) | Term::Literal(_) => {}
+        }
+    }
+
+    /// Finds the canonical serialization and the canonical label of every local node
+    fn canonical_labels(&self) -> (String, Vec<String>) {
+        let colors = self
+            .is_variable
+            .iter()
+            .map(|&variable| digest(if variable { "variable" } else { "blank" }))
+            .collect();
+
+        let mut state = SearchState {
+            path: Vec::new(),
+            first: None,
+            best: None,
+            automorphisms: Vec::new(),
+        };
+        self.search(colors, &mut state);
+        let leaf = state.best.expect("the search always reaches a leaf");
+
+        let mut labels = vec![String::new(); self.is_variable.len()];
+        for (node, label) in self.label_order(&leaf.order) {
+            labels[node] = label;
+        }
+        (leaf.form, labels)
+    }
+
+    /// Assigns labels in order, numbering blank nodes and variables separately
+    fn label_order(&self, order: &[usize]) -> Vec<(usize, String)> {
+        let (mut blanks, mut variables) = (0, 0);
+        order
+            .iter()
+            .map(|&node| {
+                let counter = if self.is_variable[node] { &mut variables } else { &mut blanks };
+                let label = format!("{}{}", LABEL_PREFIX, counter);
+                *counter += 1;
+                (node, label)
+            })
+            .collect()
+    }
+
+    /// Refines `colors` and explores every way of breaking the remaining ties
+    ///
+    /// `state.path` holds the nodes individualized on the way here. Two leaves
+    /// with the same serialization reveal an automorphism, which is used to
+    /// skip candidates known to lead to the same leaves. Returns the depth to
+    /// backtrack to when the whole subtree is known to mirror an explored one.
+    fn search(&self, colors: Vec<String>, state: &mut SearchState) -> Option<usize> {
+        let colors = self.refine(colors);
+
+        let mut cells: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (node, color) in colors.iter().enumerate() {
+            cells.entry(color).or_default().push(node);
+        }
+
+        let tied = cells
+            .iter()
+            .filter(|(_, nodes)| nodes.len() > 1)
+            .min_by(|(a_color, a), (b_color, b)| a.len().cmp(&b.len()).then(a_color.cmp(b_color)));
+
+        let Some((_, cell)) = tied else {
+            let mut order: Vec<usize> = (0..colors.len()).collect();
+            order.sort_by(|&a, &b| colors[a].cmp(&colors[b]));
+            return self.record_leaf(order, state);
+        };
+
+        let depth = state.path.len();
+        let mut explored: Vec<usize> = Vec::new();
+        for &candidate in cell {
+            if !explored.is_empty() {
+                let orbits = Orbits::new(colors.len(), &state.automorphisms, &state.path);
+                if explored.iter().any(|&done| orbits.same(done, candidate)) {
+                    continue;
+                }
+            }
+
+            let mut individualized = colors.clone();
+            individualized[candidate] = digest(&format!("{}*{}", colors[candidate], depth));
+            state.path.push(candidate);
+            let backtrack = self.search(individualized, state);
+            state.path.pop();
+            explored.push(candidate);
+
+            if let Some(target) = backtrack {
+                if target < depth {
+                    return Some(target);
+                }
+            }
+        }
+        None
+    }
+
+    /// Keeps the leaf if it is the smallest so far, or records the automorphism if it ties
+    ///
+    /// A leaf equivalent to the first one found means the subtree where the
+    /// two paths diverge mirrors the first path, so the search backtracks there.
+    fn record_leaf(&self, order: Vec<usize>, state: &mut SearchState) -> Option<usize> {
+        let mut names = vec![String::new(); order.len()];
+        for (node, label) in self.label_order(&order) {
+            names[node] = self.written(node, &label);
+        }
+        let form = self.serialize_root(&names);
+
+        let Some((first, first_path)) = &state.first else {
+            state.first = Some((Leaf { form: form.clone(), order: order.clone() }, state.path.clone()));
+            state.best = Some(Leaf { form, order });
+            return None;
+        };
+
+        for leaf in [first, state.best.as_ref().expect("the best leaf is set with the first")] {
+            if leaf.form == form {
+                let mut automorphism = vec![0; order.len()];
+                for (&from, &to) in leaf.order.iter().zip(&order) {
+                    automorphism[from] = to;
+                }
+                state.automorphisms.push(automorphism);
+
+                if leaf.form == first.form {
+                    let common = first_path.iter().zip(&state.path).take_while(|(a, b)| a == b).count();
+                    return Some(common);
+                }
+                return None;
+            }
+        }
+
+        if state.best.as_ref().is_some_and(|best| form < best.form) {
+            state.best = Some(Leaf { form, order });
+        }
+        None
+    }
+
+    /// Repeatedly rehashes every node from its statements until no new node is distinguished
+    fn refine(&self, mut colors: Vec<String>) -> Vec<String> {
+        let mut distinct = count_distinct(&colors);
+        loop {
+            let names: Vec<String> = colors
+                .iter()
+                .enumerate()
+                .map(|(node, color)| self.written(node, color))
+                .collect();
+
+            let refined: Vec<String> = (0..colors.len())
+                .map(|node| {
+                    let mut signature: Vec<String> =
+                        self.quantifiers[node].iter().map(|quantifier| quantifier.to_string()).collect();
+                    for &index in &self.occurrences[node] {
+                        let mut out = String::new();
+                        self.write_statement(self.statements[index], &names, Some(node), &mut out);
+                        signature.push(out);
+                    }
+                    signature.sort_unstable();
+                    digest(&format!("{}|{}", colors[node], signature.join("\n")))
+                })
+                .collect();
+
+            let refined_distinct = count_distinct(&refined);
+            if refined_distinct == distinct {
+                return colors;
+            }
+            colors = refined;
+            distinct = refined_distinct;
+        }
+    }
+
+    /// How a local node is written given its color or label
+    fn written(&self, node: usize, name: &str) -> String {
+        if self.is_variable[node] {
+            format!("?{}", name)
+        } else {
+            format!("_:{}", name)
+        }
+    }
+
+    fn node_of(&self, term: &Term) -> Option<usize> {
+        match term {
+            Term::BlankNode(node) => self.blanks.get(node.as_str()).copied(),
+            Term::Variable(var) => self.variables.get(var.as_str()).copied(),
+            _ => None,
+        }
+    }
+
+    /// Writes the top-level formula, one quantifier declaration or statement per line
+    fn serialize_root(&self, names: &[String]) -> String {
+        let mut out = String::new();
+        for part in self.formula_parts(self.root, names, None) {
+            out.push_str(&part);
+            out.push('\n');
+        }
+        out
+    }
+
+    fn formula_parts(&self, formula: &Formula, names: &[String], focus: Option<usize>) -> Vec<String> {
+        let mut parts = Vec::new();
+        for (keyword, vars) in [("@forAll", &formula.universal_vars), ("@forSome", &formula.existential_vars)] {
+            if vars.is_empty() {
+                continue;
+            }
+            let mut written: Vec<String> = vars
+                .iter()
+                .map(|var| {
+                    let node = self.variables[var.as_str()];
+                    self.name(node, names, focus, "?@")
+                })
+                .collect();
+            written.sort_unstable();
+            parts.push(format!("{} {} .", keyword, written.join(", ")));
+        }
+
+        let mut statements: Vec<String> = formula
+            .statements
+            .iter()
+            .map(|statement| {
+                let mut out = String::new();
+                self.write_statement(statement, names, focus, &mut out);
+                out
+            })
+            .collect();
+        statements.sort_unstable();
+        parts.extend(statements);
+        parts
+    }
+
+    fn name(&self, node: usize, names: &[String], focus: Option<usize>, marker: &str) -> String {
+        if focus == Some(node) {
+            marker.to_string()
+        } else {
+            names[node].clone()
+        }
+    }
+
+    fn write_statement(&self, statement: &Statement, names: &[String], focus: Option<usize>, out: &mut String) {
+        self.write_term(&statement.subject, names, focus, out);
+        out.push(' ');
+        self.write_term(&statement.predicate, names, focus, out);
+        out.push(' ');
+        self.write_term(&statement.object, names, focus, out);
+        out.push_str(" .");
+    }
+
+    /// Writes a term with local nodes replaced by `names`, and `focus` by a marker
+    fn write_term(&self, term: &Term, names: &[String], focus: Option<usize>, out: &mut String) {
+        match term {
+            Term::Iri(iri) => write!(out, "{}", iri).unwrap(),
+            Term::Literal(literal) => write!(out, "{}", literal).unwrap(),
+            Term::BlankNode(_) | Term::Variable(_) => {
+                let node = self.node_of(term).expect("every local node is registered");
+                let marker = if self.is_variable[node] { "?@" } else { "_:@" };
+                out.push_str(&self.name(node, names, focus, marker));
+            }
+            Term::List(items) => {
+                out.push('(');
+                for item in items {
+                    out.push(' ');
+                    self.write_term(item, names, focus, out);
+                }
+                out.push_str(" )");
+            }
+            Term::Formula(formula) => {
+                out.push('{');
+                for part in self.formula_parts(formula, names, focus) {
+                    out.push(' ');
+                    out.push_str(&part);
+                }
+                out.push_str(" }");
+            }
+        }
+    }
+
+    /// Rebuilds a formula with every local node renamed to its canonical label
+    fn relabel(&self, formula: &Formula, labels: &[String]) -> Formula {
+        let rename = |var: &String| labels[self.variables[var.as_str()]].clone();
+        Formula {
+            statements: formula
+                .statements
+                .iter()
+                .map(|statement| Statement {
+                    subject: self.relabel_term(&statement.subject, labels),
+                    predicate: self.relabel_term(&statement.predicate, labels),
+                    object: self.relabel_term(&statement.object, labels),
+                })
+                .collect(),
+            universal_vars: formula.universal_vars.iter().map(rename).collect(),
+            existential_vars: formula.existential_vars.iter().map(rename).collect(),
+        }
+    }
+
+    fn relabel_term(&self, term: &Term, labels: &[String]) -> Term {
+        match term {
+            Term::BlankNode(_) => {
+                let node = self.node_of(term).expect("every local node is registered");
+                Term::BlankNode(BlankNode::new_unchecked(labels[node].clone()))
+            }
+            Term::Variable(_) => {
+                let node = self.node_of(term).expect("every local node is registered");
+                Term::Variable(labels[node].clone())
+            }
+            Term::List(items) => Term::List(items.iter().map(|item| self.relabel_term(item, labels)).collect()),
+            Term::Formula(formula) => Term::Formula(Arc::new(self.relabel(formula, labels))),
+            Term::Iri(_) | Term::Literal(_) => term.clone(),
+        }
+    }
+}
+
+/// Orbits of the local nodes under the known automorphisms that fix `prefix`
+struct Orbits {
+    parent: Vec<usize>,
+}
+
+impl Orbits {
+    fn new(size: usize, automorphisms: &[Vec<usize>], prefix: &[usize]) -> Self {
+        let mut orbits = Orbits { parent: (0..size).collect() };
+        for automorphism in automorphisms {
+            if prefix.iter().all(|&node| automorphism[node] == node) {
+                for (from, &to) in automorphism.iter().enumerate() {
+                    let (a, b) = (orbits.find(from), orbits.find(to));
+                    orbits.parent[a] = b;
+                }
+            }
+        }
+        orbits
+    }
+
+    fn find(&self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn same(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+/// Collects the formulas directly nested in a term, looking inside lists
+fn nested_formulas<'a>(term: &'a Term, nested: &mut Vec<&'a Formula>) {
+    match term {
+        Term::Formula(formula) => nested.push(formula),
+        Term::List(items) => {
+            for item in items {
+                nested_formulas(item, nested);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn sorted(vars: &HashSet<String>) -> impl Iterator<Item = &str> {
+    let mut vars: Vec<&str> = vars.iter().map(String::as_str).collect();
+    vars.sort_unstable();
+    vars.into_iter()
+}
+
+fn count_distinct(colors: &[String]) -> usize {
+    colors.iter().collect::<HashSet<_>>().len()
+}
+
+/// Hex-encoded SHA-256 digest, as used for hashes in RDFC-1.0
+fn digest(input: &str) -> String {
+    let mut hex = String::with_capacity(64);
+    for byte in Sha256::digest(input.as_bytes()) {
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+    hex
+}
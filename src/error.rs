@@ -1,3 +1,5 @@
+use std::fmt;
+
 use thiserror::Error;
 
 /// Represents errors that can occur in the N3 proof engine
@@ -5,7 +7,7 @@ use thiserror::Error;
 pub enum Error {
     /// Error during parsing of N3 syntax
     #[error("Parsing error: {0}")]
-    ParseError(String),
+    ParseError(ParseError),
 
     /// Error during reasoning or proof generation
     #[error("Reasoning error: {0}")]
@@ -19,6 +21,14 @@ pub enum Error {
     #[error("Proof verification error: {0}")]
     ProofVerificationError(String),
 
+    /// Error when rules cannot be saturated in strata, because a negation depends on its own conclusions
+    #[error("Stratification error: {0}")]
+    StratificationError(String),
+
+    /// Error raised by a builtin predicate
+    #[error("Builtin error: {0}")]
+    BuiltinError(#[from] BuiltinError),
+
     /// Error forwarded from RDF libraries
     #[error("RDF error: {0}")]
     RdfError(#[from] anyhow::Error),
@@ -28,5 +38,172 @@ pub enum Error {
     IoError(#[from] std::io::Error),
 }
 
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::ParseError(error)
+    }
+}
+
+/// An error raised while evaluating a builtin predicate
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BuiltinError {
+    /// The arguments the builtin needs are not bound yet
+    ///
+    /// The reasoner evaluates the builtin again once other premises have
+    /// bound more variables, and treats it as failing if none can.
+    #[error("{builtin} needs more bound arguments")]
+    InsufficientlyBound {
+        /// IRI of the builtin
+        builtin: String,
+    },
+
+    /// An argument has a value the builtin cannot work with
+    #[error("{builtin}: {message}")]
+    InvalidArgument {
+        /// IRI of the builtin
+        builtin: String,
+
+        /// Description of the problem
+        message: String,
+    },
+}
+
+impl BuiltinError {
+    /// Create an [`BuiltinError::InsufficientlyBound`] error
+    pub fn insufficiently_bound(builtin: &str) -> Self {
+        BuiltinError::InsufficientlyBound { builtin: builtin.to_string() }
+    }
+
+    /// Create an [`BuiltinError::InvalidArgument`] error
+    pub fn invalid_argument(builtin: &str, message: impl Into<String>) -> Self {
+        BuiltinError::InvalidArgument { builtin: builtin.to_string(), message: message.into() }
+    }
+}
+
 /// Shorthand for Result with our Error type
-pub type Result<T> = std::result::Result<T, Error>; 
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A location in an N3 source document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Byte offset from the start of the document
+    pub offset: usize,
+
+    /// Line number (1-based)
+    pub line: usize,
+
+    /// Column number in characters (1-based)
+    pub column: usize,
+
+    /// Name of the file the document was read from, if known
+    pub file: Option<String>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A syntax error found while parsing an N3 document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Description of the problem
+    pub message: String,
+
+    /// Where the problem was found, if it relates to a position in the source
+    pub location: Option<SourceLocation>,
+
+    /// The offending token as it appears in the source
+    pub token: Option<String>,
+}
+
+impl ParseError {
+    /// Create a parse error that is not tied to a source position
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            message: message.into(),
+            location: None,
+            token: None,
+        }
+    }
+
+    /// Set the location of the error
+    pub fn at(mut self, location: SourceLocation) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Set the offending token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Record the name of the file the error was found in
+    pub fn in_file(mut self, file: &str) -> Self {
+        if let Some(location) = &mut self.location {
+            location.file = Some(file.to_string());
+        }
+        self
+    }
+
+    /// Render the error with a caret-annotated snippet of the given source
+    ///
+    /// `source` must be the document the error was reported for. Errors without
+    /// a location are rendered as the bare message.
+    pub fn render(&self, source: &str) -> String {
+        let mut output = format!("error: {}\n", self.message);
+
+        let location = match &self.location {
+            Some(location) => location,
+            None => return output,
+        };
+        output.push_str(&format!(" --> {}\n", location));
+
+        let line = match source.lines().nth(location.line.saturating_sub(1)) {
+            Some(line) => line.trim_end_matches('\r'),
+            None => return output,
+        };
+
+        // Columns are 1-based, but a hand-built location may say 0
+        let skipped = location.column.saturating_sub(1);
+        let remaining = line.chars().count().saturating_sub(skipped);
+        let width = self
+            .token
+            .as_ref()
+            .map_or(1, |token| token.chars().count())
+            .min(remaining)
+            .max(1);
+
+        let gutter = " ".repeat(location.line.to_string().len());
+        let padding: String = line
+            .chars()
+            .take(skipped)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        output.push_str(&format!("{} |\n", gutter));
+        output.push_str(&format!("{} | {}\n", location.line, line));
+        output.push_str(&format!("{} | {}{}\n", gutter, padding, "^".repeat(width)));
+        output
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(location) = &self.location {
+            match &location.file {
+                Some(file) => write!(f, " at {}:{}:{}", file, location.line, location.column)?,
+                None => write!(f, " at line {}, column {}", location.line, location.column)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,163 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::model::{Formula, Statement, Term};
+use crate::store::{Triple, TripleStore};
+
+/// Compact identifier of a term interned in an [`Interner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TermId(u32);
+
+impl TermId {
+    /// Position of the term in its interner
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for TermId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A statement whose terms have been replaced by their interned ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdTriple {
+    /// Subject of the statement
+    pub subject: TermId,
+
+    /// Predicate of the statement
+    pub predicate: TermId,
+
+    /// Object of the statement
+    pub object: TermId,
+}
+
+impl Triple for IdTriple {
+    type Term = TermId;
+
+    fn from_terms(subject: TermId, predicate: TermId, object: TermId) -> Self {
+        IdTriple { subject, predicate, object }
+    }
+
+    fn subject(&self) -> &TermId {
+        &self.subject
+    }
+
+    fn predicate(&self) -> &TermId {
+        &self.predicate
+    }
+
+    fn object(&self) -> &TermId {
+        &self.object
+    }
+}
+
+/// Dictionary that maps each distinct term to a [`TermId`] and back
+///
+/// Every term is stored once, so a store of [`IdTriple`]s only pays for
+/// three integers per statement however often an IRI or literal repeats.
+/// Nested formulas and lists are interned as single terms.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    /// Interned terms, indexed by id
+    terms: Vec<Term>,
+
+    /// Ids of the interned terms, bucketed by hash so each term is stored only once
+    buckets: HashMap<u64, Vec<TermId>>,
+}
+
+impl Interner {
+    /// Creates a new empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct terms interned
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Returns true if no term has been interned
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the id of a term, interning it first if needed
+    pub fn intern(&mut self, term: &Term) -> TermId {
+        let hash = hash_term(term);
+        if let Some(id) = self.lookup(hash, term) {
+            return id;
+        }
+
+        let id = TermId(u32::try_from(self.terms.len()).expect("too many distinct terms to intern"));
+        self.terms.push(term.clone());
+        self.buckets.entry(hash).or_default().push(id);
+        id
+    }
+
+    /// Returns the id of a term if it has been interned
+    pub fn get(&self, term: &Term) -> Option<TermId> {
+        self.lookup(hash_term(term), term)
+    }
+
+    /// Returns the term an id stands for
+    ///
+    /// Panics if the id was not produced by this interner.
+    pub fn resolve(&self, id: TermId) -> &Term {
+        &self.terms[id.index()]
+    }
+
+    /// Interns the terms of a statement
+    pub fn intern_statement(&mut self, statement: &Statement) -> IdTriple {
+        IdTriple {
+            subject: self.intern(&statement.subject),
+            predicate: self.intern(&statement.predicate),
+            object: self.intern(&statement.object),
+        }
+    }
+
+    /// Returns the interned form of a statement if all its terms have been interned
+    pub fn get_statement(&self, statement: &Statement) -> Option<IdTriple> {
+        Some(IdTriple {
+            subject: self.get(&statement.subject)?,
+            predicate: self.get(&statement.predicate)?,
+            object: self.get(&statement.object)?,
+        })
+    }
+
+    /// Converts an interned statement back to terms
+    pub fn resolve_statement(&self, triple: &IdTriple) -> Statement {
+        Statement {
+            subject: self.resolve(triple.subject).clone(),
+            predicate: self.resolve(triple.predicate).clone(),
+            object: self.resolve(triple.object).clone(),
+        }
+    }
+
+    /// Converts a store of interned statements back to a formula
+    pub fn resolve_store(&self, store: &TripleStore<IdTriple>) -> Formula {
+        let mut formula = Formula::new();
+        for triple in store {
+            formula.add_statement(self.resolve_statement(triple));
+        }
+        formula
+    }
+
+    fn lookup(&self, hash: u64, term: &Term) -> Option<TermId> {
+        self.buckets
+            .get(&hash)?
+            .iter()
+            .copied()
+            .find(|&id| self.terms[id.index()] == *term)
+    }
+}
+
+fn hash_term(term: &Term) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    term.hash(&mut hasher);
+    hasher.finish()
+}
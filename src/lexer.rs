@@ -0,0 +1,619 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::error::{Error, ParseError, Result, SourceLocation};
+
+/// A position in the source document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Position {
+    /// Byte offset from the start of the document
+    pub offset: usize,
+
+    /// Line number (1-based)
+    pub line: usize,
+
+    /// Column number in characters (1-based)
+    pub column: usize,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl From<Position> for SourceLocation {
+    fn from(position: Position) -> Self {
+        SourceLocation {
+            offset: position.offset,
+            line: position.line,
+            column: position.column,
+            file: None,
+        }
+    }
+}
+
+/// A lexical token of the N3 grammar
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// `<...>` with escapes decoded but not yet resolved against a base
+    IriRef(String),
+
+    /// `prefix:local`, with the prefix possibly empty
+    PrefixedName(String, String),
+
+    /// `_:label`
+    BlankNodeLabel(String),
+
+    /// `?name`
+    Variable(String),
+
+    /// A quoted string with an optional language tag
+    String {
+        value: String,
+        language: Option<String>,
+    },
+
+    Integer(String),
+    Decimal(String),
+    Double(String),
+
+    /// `@word`, e.g. `@prefix` or `@forAll`
+    AtKeyword(String),
+
+    /// A bare word such as `a`, `is`, `true` or `PREFIX`
+    Bareword(String),
+
+    Dot,
+    Semicolon,
+    Comma,
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+    OpenBrace,
+    CloseBrace,
+    DoubleCaret,
+    Caret,
+    Bang,
+    Equals,
+    Implies,
+    ImpliedBy,
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::IriRef(iri) => write!(f, "<{}>", iri),
+            Token::PrefixedName(prefix, local) => write!(f, "{}:{}", prefix, local),
+            Token::BlankNodeLabel(label) => write!(f, "_:{}", label),
+            Token::Variable(name) => write!(f, "?{}", name),
+            Token::String { value, language } => {
+                write!(f, "\"{}\"", value)?;
+                if let Some(lang) = language {
+                    write!(f, "@{}", lang)?;
+                }
+                Ok(())
+            }
+            Token::Integer(n) | Token::Decimal(n) | Token::Double(n) => write!(f, "{}", n),
+            Token::AtKeyword(word) => write!(f, "@{}", word),
+            Token::Bareword(word) => write!(f, "{}", word),
+            Token::Dot => write!(f, "."),
+            Token::Semicolon => write!(f, ";"),
+            Token::Comma => write!(f, ","),
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+            Token::OpenBrace => write!(f, "{{"),
+            Token::CloseBrace => write!(f, "}}"),
+            Token::DoubleCaret => write!(f, "^^"),
+            Token::Caret => write!(f, "^"),
+            Token::Bang => write!(f, "!"),
+            Token::Equals => write!(f, "="),
+            Token::Implies => write!(f, "=>"),
+            Token::ImpliedBy => write!(f, "<="),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Tokenizer for N3 documents
+///
+/// The lexer decodes UTF-8 incrementally from the underlying reader and only
+/// keeps a few characters of lookahead, so it never holds the whole document.
+pub(crate) struct Lexer<R: Read> {
+    reader: BufReader<R>,
+    lookahead: VecDeque<char>,
+    position: Position,
+}
+
+impl<R: Read> Lexer<R> {
+    /// Create a new lexer over the given input
+    pub fn new(input: R) -> Self {
+        Lexer {
+            reader: BufReader::new(input),
+            lookahead: VecDeque::new(),
+            position: Position::default(),
+        }
+    }
+
+    /// Read the next token together with the position where it starts
+    ///
+    /// A lexical error always consumes at least one character, so callers that
+    /// recover from errors are guaranteed to make progress.
+    pub fn next_token(&mut self) -> Result<(Token, Position)> {
+        self.skip_whitespace_and_comments()?;
+        let start = self.position;
+
+        let result = self.lex_token(start);
+        if result.is_err() && self.position == start {
+            self.advance()?;
+        }
+        result
+    }
+
+    fn lex_token(&mut self, start: Position) -> Result<(Token, Position)> {
+        let c = match self.peek(0)? {
+            Some(c) => c,
+            None => return Ok((Token::Eof, start)),
+        };
+
+        let token = match c {
+            '<' => {
+                if self.peek(1)? == Some('=') && is_implied_by_end(self.peek(2)?) {
+                    self.advance_by(2)?;
+                    Token::ImpliedBy
+                } else {
+                    self.lex_iri_ref()?
+                }
+            }
+            '"' | '\'' => self.lex_string(c)?,
+            '_' if self.peek(1)? == Some(':') => {
+                self.advance_by(2)?;
+                let label = self.read_name(false)?;
+                if label.is_empty() {
+                    return Err(self.error_at(start, "Expected a blank node label after '_:'"));
+                }
+                Token::BlankNodeLabel(label)
+            }
+            '?' => {
+                self.advance()?;
+                let name = self.read_name(false)?;
+                if name.is_empty() {
+                    return Err(self.error_at(start, "Expected a variable name after '?'"));
+                }
+                Token::Variable(name)
+            }
+            '@' => {
+                self.advance()?;
+                let mut word = String::new();
+                while let Some(c) = self.peek(0)? {
+                    if c.is_ascii_alphanumeric() || c == '-' {
+                        word.push(c);
+                        self.advance()?;
+                    } else {
+                        break;
+                    }
+                }
+                if word.is_empty() {
+                    return Err(self.error_at(start, "Expected a keyword after '@'"));
+                }
+                Token::AtKeyword(word)
+            }
+            '+' | '-' | '0'..='9' => self.lex_number(start)?,
+            '.' if matches!(self.peek(1)?, Some('0'..='9')) => self.lex_number(start)?,
+            '.' => self.single(Token::Dot)?,
+            ';' => self.single(Token::Semicolon)?,
+            ',' => self.single(Token::Comma)?,
+            '[' => self.single(Token::OpenBracket)?,
+            ']' => self.single(Token::CloseBracket)?,
+            '(' => self.single(Token::OpenParen)?,
+            ')' => self.single(Token::CloseParen)?,
+            '{' => self.single(Token::OpenBrace)?,
+            '}' => self.single(Token::CloseBrace)?,
+            '!' => self.single(Token::Bang)?,
+            '^' => {
+                if self.peek(1)? == Some('^') {
+                    self.advance_by(2)?;
+                    Token::DoubleCaret
+                } else {
+                    self.single(Token::Caret)?
+                }
+            }
+            '=' => {
+                if self.peek(1)? == Some('>') {
+                    self.advance_by(2)?;
+                    Token::Implies
+                } else {
+                    self.single(Token::Equals)?
+                }
+            }
+            ':' => {
+                self.advance()?;
+                let local = self.read_local_name()?;
+                Token::PrefixedName(String::new(), local)
+            }
+            c if is_pn_chars_base(c) => {
+                let prefix = self.read_name(false)?;
+                if self.peek(0)? == Some(':') {
+                    self.advance()?;
+                    let local = self.read_local_name()?;
+                    Token::PrefixedName(prefix, local)
+                } else {
+                    Token::Bareword(prefix)
+                }
+            }
+            c => {
+                let error = ParseError::new(format!("Unexpected character '{}'", c))
+                    .at(start.into())
+                    .with_token(c);
+                return Err(error.into());
+            }
+        };
+
+        Ok((token, start))
+    }
+
+    fn single(&mut self, token: Token) -> Result<Token> {
+        self.advance()?;
+        Ok(token)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<()> {
+        while let Some(c) = self.peek(0)? {
+            if c.is_whitespace() {
+                self.advance()?;
+            } else if c == '#' {
+                while let Some(c) = self.peek(0)? {
+                    if c == '\n' || c == '\r' {
+                        break;
+                    }
+                    self.advance()?;
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn lex_iri_ref(&mut self) -> Result<Token> {
+        let start = self.position;
+        self.advance()?;
+        let mut iri = String::new();
+        loop {
+            match self.advance()? {
+                None => return Err(self.error_at(start, "Unterminated IRI reference")),
+                Some('>') => break,
+                Some('\\') => {
+                    let escape_pos = self.position;
+                    let c = match self.advance()? {
+                        Some('u') => self.read_hex_escape(4, escape_pos)?,
+                        Some('U') => self.read_hex_escape(8, escape_pos)?,
+                        _ => return Err(self.error_at(escape_pos, "Invalid escape in IRI reference")),
+                    };
+                    iri.push(c);
+                }
+                Some(c) if c.is_whitespace() || matches!(c, '<' | '"' | '{' | '}' | '|' | '^' | '`') => {
+                    return Err(self.error_at(
+                        start,
+                        &format!("Invalid character '{}' in IRI reference", c.escape_default()),
+                    ));
+                }
+                Some(c) => iri.push(c),
+            }
+        }
+        Ok(Token::IriRef(iri))
+    }
+
+    fn lex_string(&mut self, quote: char) -> Result<Token> {
+        let start = self.position;
+        let long = self.peek(1)? == Some(quote) && self.peek(2)? == Some(quote);
+        let mut value = String::new();
+
+        if long {
+            self.advance_by(3)?;
+            loop {
+                match self.peek(0)? {
+                    None => return Err(self.error_at(start, "Unterminated long string literal")),
+                    Some(c) if c == quote
+                        && self.peek(1)? == Some(quote)
+                        && self.peek(2)? == Some(quote) =>
+                    {
+                        self.advance_by(3)?;
+                        break;
+                    }
+                    Some('\\') => value.push(self.read_string_escape()?),
+                    Some(c) => {
+                        self.advance()?;
+                        value.push(c);
+                    }
+                }
+            }
+        } else {
+            self.advance()?;
+            loop {
+                match self.peek(0)? {
+                    None | Some('\n') | Some('\r') => {
+                        return Err(self.error_at(start, "Unterminated string literal"));
+                    }
+                    Some(c) if c == quote => {
+                        self.advance()?;
+                        break;
+                    }
+                    Some('\\') => value.push(self.read_string_escape()?),
+                    Some(c) => {
+                        self.advance()?;
+                        value.push(c);
+                    }
+                }
+            }
+        }
+
+        // A language tag must follow the closing quote immediately
+        let mut language = None;
+        if self.peek(0)? == Some('@') && matches!(self.peek(1)?, Some(c) if c.is_ascii_alphabetic()) {
+            self.advance()?;
+            let mut tag = String::new();
+            while let Some(c) = self.peek(0)? {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    tag.push(c);
+                    self.advance()?;
+                } else {
+                    break;
+                }
+            }
+            language = Some(tag);
+        }
+
+        Ok(Token::String { value, language })
+    }
+
+    fn read_string_escape(&mut self) -> Result<char> {
+        let escape_pos = self.position;
+        self.advance()?;
+        match self.advance()? {
+            Some('t') => Ok('\t'),
+            Some('b') => Ok('\u{8}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('f') => Ok('\u{c}'),
+            Some('"') => Ok('"'),
+            Some('\'') => Ok('\''),
+            Some('\\') => Ok('\\'),
+            Some('u') => self.read_hex_escape(4, escape_pos),
+            Some('U') => self.read_hex_escape(8, escape_pos),
+            Some(c) => Err(self.error_at(escape_pos, &format!("Invalid string escape '\\{}'", c))),
+            None => Err(self.error_at(escape_pos, "Unterminated string escape")),
+        }
+    }
+
+    fn read_hex_escape(&mut self, digits: usize, escape_pos: Position) -> Result<char> {
+        let mut code = 0u32;
+        for _ in 0..digits {
+            let digit = self
+                .advance()?
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error_at(escape_pos, "Invalid hexadecimal escape"))?;
+            code = code * 16 + digit;
+        }
+        char::from_u32(code).ok_or_else(|| self.error_at(escape_pos, "Escape is not a valid code point"))
+    }
+
+    fn lex_number(&mut self, start: Position) -> Result<Token> {
+        let mut text = String::new();
+        if let Some(sign @ ('+' | '-')) = self.peek(0)? {
+            text.push(sign);
+            self.advance()?;
+        }
+
+        let mut has_digits = self.read_digits(&mut text)? > 0;
+        let mut is_decimal = false;
+        if self.peek(0)? == Some('.') && matches!(self.peek(1)?, Some('0'..='9')) {
+            text.push('.');
+            self.advance()?;
+            has_digits |= self.read_digits(&mut text)? > 0;
+            is_decimal = true;
+        }
+
+        if !has_digits {
+            return Err(self.error_at(start, "Expected digits in numeric literal"));
+        }
+
+        if let Some(e @ ('e' | 'E')) = self.peek(0)? {
+            text.push(e);
+            self.advance()?;
+            if let Some(sign @ ('+' | '-')) = self.peek(0)? {
+                text.push(sign);
+                self.advance()?;
+            }
+            if self.read_digits(&mut text)? == 0 {
+                return Err(self.error_at(start, "Expected digits in exponent"));
+            }
+            return Ok(Token::Double(text));
+        }
+
+        if is_decimal {
+            Ok(Token::Decimal(text))
+        } else {
+            Ok(Token::Integer(text))
+        }
+    }
+
+    fn read_digits(&mut self, text: &mut String) -> Result<usize> {
+        let mut count = 0;
+        while let Some(c @ '0'..='9') = self.peek(0)? {
+            text.push(c);
+            self.advance()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Read a name made of PN_CHARS, allowing inner but not trailing dots
+    fn read_name(&mut self, allow_colon: bool) -> Result<String> {
+        let mut name = String::new();
+        while let Some(c) = self.peek(0)? {
+            if is_pn_chars(c) || (allow_colon && c == ':') {
+                name.push(c);
+                self.advance()?;
+            } else if c == '.' && !name.is_empty() {
+                match self.peek(1)? {
+                    Some(next) if is_pn_chars(next) || (allow_colon && next == ':') => {
+                        name.push('.');
+                        self.advance()?;
+                    }
+                    _ => break,
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(name)
+    }
+
+    /// Read the local part of a prefixed name, decoding `\` escapes
+    fn read_local_name(&mut self) -> Result<String> {
+        let mut name = String::new();
+        loop {
+            match self.peek(0)? {
+                Some(c) if is_pn_chars(c) || c == ':' => {
+                    name.push(c);
+                    self.advance()?;
+                }
+                Some('%') => {
+                    let escape_pos = self.position;
+                    name.push('%');
+                    self.advance()?;
+                    for _ in 0..2 {
+                        match self.advance()? {
+                            Some(h) if h.is_ascii_hexdigit() => name.push(h),
+                            _ => return Err(self.error_at(escape_pos, "Invalid percent encoding in local name")),
+                        }
+                    }
+                }
+                Some('\\') => {
+                    let escape_pos = self.position;
+                    self.advance()?;
+                    match self.advance()? {
+                        Some(c) if "_~.-!$&'()*+,;=/?#@%".contains(c) => name.push(c),
+                        _ => return Err(self.error_at(escape_pos, "Invalid escape in local name")),
+                    }
+                }
+                Some('.') if !name.is_empty() => match self.peek(1)? {
+                    Some(next) if is_pn_chars(next) || matches!(next, ':' | '%' | '\\') => {
+                        name.push('.');
+                        self.advance()?;
+                    }
+                    _ => break,
+                },
+                _ => break,
+            }
+        }
+        Ok(name)
+    }
+
+    /// Peek at the character `n` positions ahead without consuming it
+    fn peek(&mut self, n: usize) -> Result<Option<char>> {
+        while self.lookahead.len() <= n {
+            match self.read_char()? {
+                Some(c) => self.lookahead.push_back(c),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(self.lookahead[n]))
+    }
+
+    /// Consume one character, keeping track of the current position
+    fn advance(&mut self) -> Result<Option<char>> {
+        self.peek(0)?;
+        let c = self.lookahead.pop_front();
+        if let Some(c) = c {
+            self.position.offset += c.len_utf8();
+            if c == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+        }
+        Ok(c)
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<()> {
+        for _ in 0..n {
+            self.advance()?;
+        }
+        Ok(())
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        let byte = match self.reader.fill_buf()?.first() {
+            Some(&b) => b,
+            None => return Ok(None),
+        };
+        self.reader.consume(1);
+        Ok(Some(byte))
+    }
+
+    /// Decode a single UTF-8 character from the reader
+    fn read_char(&mut self) -> Result<Option<char>> {
+        let first = match self.read_byte()? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+
+        let width = match first {
+            0x00..=0x7F => return Ok(Some(first as char)),
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return Err(self.error_at(self.position, "Invalid UTF-8 in input")),
+        };
+
+        let mut buf = [first, 0, 0, 0];
+        for slot in buf.iter_mut().take(width).skip(1) {
+            *slot = self
+                .read_byte()?
+                .ok_or_else(|| self.error_at(self.position, "Truncated UTF-8 sequence in input"))?;
+        }
+
+        std::str::from_utf8(&buf[..width])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .map(Some)
+            .ok_or_else(|| self.error_at(self.position, "Invalid UTF-8 in input"))
+    }
+
+    fn error_at(&self, position: Position, message: &str) -> Error {
+        ParseError::new(message).at(position.into()).into()
+    }
+}
+
+/// `<=` is only an operator when it is not the start of an IRI reference
+fn is_implied_by_end(next: Option<char>) -> bool {
+    match next {
+        None => true,
+        Some(c) => c.is_whitespace() || matches!(c, '{' | '[' | '(' | '?' | '_' | '"' | '<'),
+    }
+}
+
+fn is_pn_chars_base(c: char) -> bool {
+    c.is_alphabetic()
+}
+
+fn is_pn_chars(c: char) -> bool {
+    c.is_alphanumeric()
+        || c == '_'
+        || c == '-'
+        || c == '\u{B7}'
+        || ('\u{300}'..='\u{36F}').contains(&c)
+        || ('\u{203F}'..='\u{2040}').contains(&c)
+}
@@ -3,18 +3,40 @@
 // This library implements a proof engine for Notation3 (N3), an extension of RDF
 // that adds logical expressions and rules for the Semantic Web.
 
+mod builtin;
+mod builtin_list;
+mod builtin_log;
+mod builtin_math;
+mod builtin_string;
+mod builtin_time;
+mod canon;
 mod error;
+mod interner;
+mod lexer;
 mod model;
 mod parser;
+mod program;
 mod proof;
 mod reasoner;
+mod store;
+mod stratification;
+mod unify;
 mod utils;
+mod vocab;
 
-pub use error::Error;
+pub use builtin::{Builtin, BuiltinContext, BuiltinRegistry};
+pub use builtin_time::{Clock, FixedClock, SystemClock};
+pub use error::{BuiltinError, Error, ParseError, SourceLocation};
+pub use interner::{IdTriple, Interner, TermId};
 pub use model::{Formula, Graph, Statement, Term};
-pub use parser::{parse_n3, ParseOptions};
+pub use parser::{parse_n3, N3Parser, ParseOptions, StatementIter};
+pub use program::Program;
 pub use proof::{Proof, ProofStep};
-pub use reasoner::{ProofEngine, Rule};
+pub use reasoner::{ProofEngine, ProofLimits, ProofOutcome, ReasoningStats, Rule, RuleDirection};
+pub use store::{Triple, TripleStore};
+pub use stratification::StratificationPolicy;
+pub use unify::Substitution;
+pub use utils::{create_triple, formula_to_n3_string, formulas_equivalent};
 
 /// The main entry point for creating a new proof engine
 pub fn create_proof_engine() -> ProofEngine {
@@ -1,11 +1,14 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use oxiri::Iri;
 use oxrdf::{BlankNode, Literal, NamedNode, Subject, Term as OxTerm};
 
-use crate::error::{Error, Result};
+use crate::error::Result;
+use crate::store::TripleStore;
+use crate::vocab;
 
 /// Represents a term in N3 (can be IRI, blank node, literal, variable, or formula)
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,20 +27,53 @@ pub enum Term {
     
     /// A nested formula/graph
     Formula(Arc<Formula>),
+
+    /// A collection of terms, written `( a b c )` in N3
+    List(Vec<Term>),
 }
 
-// Custom Hash implementation to handle Formula
-impl std::hash::Hash for Term {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+// Custom Hash implementation so that formulas are hashed by content
+impl Hash for Term {
+    fn hash<H: Hasher>(&self, state: &mut H) {
         std::mem::discriminant(self).hash(state);
         match self {
             Term::Iri(iri) => iri.hash(state),
             Term::BlankNode(bn) => bn.hash(state),
             Term::Literal(lit) => lit.hash(state),
             Term::Variable(var) => var.hash(state),
-            Term::Formula(formula) => {
-                // Use the pointer for hashing, as Formula doesn't implement Hash
-                Arc::as_ptr(formula).hash(state);
+            Term::Formula(formula) => formula.hash(state),
+            Term::List(items) => items.hash(state),
+        }
+    }
+}
+
+impl Term {
+    /// Returns true if the term contains no variables, including inside lists and formulas
+    pub fn is_ground(&self) -> bool {
+        match self {
+            Term::Variable(_) => false,
+            Term::List(items) => items.iter().all(Term::is_ground),
+            Term::Formula(formula) => formula.statements.iter().all(Statement::is_ground),
+            _ => true,
+        }
+    }
+
+    /// Hash the shape of this term, treating all variables and all blank nodes alike
+    ///
+    /// Terms that only differ by a renaming of variables or blank nodes get
+    /// the same structural hash.
+    fn structural_hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Term::Iri(iri) => iri.hash(state),
+            Term::Literal(lit) => lit.hash(state),
+            Term::BlankNode(_) | Term::Variable(_) => {}
+            Term::Formula(formula) => formula.hash(state),
+            Term::List(items) => {
+                items.len().hash(state);
+                for item in items {
+                    item.structural_hash(state);
+                }
             }
         }
     }
@@ -51,6 +87,13 @@ impl fmt::Display for Term {
             Term::Literal(lit) => write!(f, "\"{}\"", lit.value()),
             Term::Variable(var) => write!(f, "?{}", var),
             Term::Formula(_) => write!(f, "{{...}}"),
+            Term::List(items) => {
+                write!(f, "(")?;
+                for item in items {
+                    write!(f, " {}", item)?;
+                }
+                write!(f, " )")
+            }
         }
     }
 }
@@ -68,6 +111,13 @@ pub struct Statement {
     pub object: Term,
 }
 
+impl Statement {
+    /// Returns true if none of the three terms contains a variable
+    pub fn is_ground(&self) -> bool {
+        self.subject.is_ground() && self.predicate.is_ground() && self.object.is_ground()
+    }
+}
+
 impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} {} {} .", self.subject, self.predicate, self.object)
@@ -75,10 +125,17 @@ impl fmt::Display for Statement {
 }
 
 /// Represents a formula (a set of statements that can be nested)
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Statements are kept in an indexed [`TripleStore`], so a formula holds each
+/// statement once. Two formulas are equal when they contain the same set of
+/// statements and quantify the same variables, regardless of statement order.
+/// Hashing is consistent with this and additionally ignores the names of
+/// variables and blank nodes, so formulas that are equal up to renaming land
+/// in the same bucket.
+#[derive(Debug, Clone)]
 pub struct Formula {
     /// The statements contained in this formula
-    pub statements: Vec<Statement>,
+    pub statements: TripleStore,
     
     /// Variables that are universally quantified in this formula
     pub universal_vars: HashSet<String>,
@@ -91,15 +148,15 @@ impl Formula {
     /// Creates a new empty formula
     pub fn new() -> Self {
         Formula {
-            statements: Vec::new(),
+            statements: TripleStore::new(),
             universal_vars: HashSet::new(),
             existential_vars: HashSet::new(),
         }
     }
     
-    /// Adds a statement to the formula
+    /// Adds a statement to the formula, ignoring duplicates
     pub fn add_statement(&mut self, statement: Statement) {
-        self.statements.push(statement);
+        self.statements.insert(statement);
     }
     
     /// Adds a universal variable to the formula
@@ -111,6 +168,216 @@ impl Formula {
     pub fn add_existential_var(&mut self, var: &str) {
         self.existential_vars.insert(var.to_string());
     }
+
+    /// Returns a copy of this formula with every list term replaced by its
+    /// rdf:first/rdf:rest encoding, for exchanging data with plain RDF tools
+    ///
+    /// Each list node becomes a fresh blank node and the empty list becomes
+    /// rdf:nil. Nested formulas are expanded recursively.
+    pub fn expand_lists(&self) -> Formula {
+        let mut expanded = Formula {
+            statements: TripleStore::new(),
+            universal_vars: self.universal_vars.clone(),
+            existential_vars: self.existential_vars.clone(),
+        };
+
+        for statement in &self.statements {
+            let subject = expand_list_term(&statement.subject, &mut expanded);
+            let predicate = expand_list_term(&statement.predicate, &mut expanded);
+            let object = expand_list_term(&statement.object, &mut expanded);
+            expanded.add_statement(Statement { subject, predicate, object });
+        }
+
+        expanded
+    }
+
+    /// Returns a copy of this formula where well-formed rdf:first/rdf:rest
+    /// chains of blank nodes are replaced by list terms
+    ///
+    /// A chain is only collapsed when every node in it is a blank node with
+    /// exactly one rdf:first and one rdf:rest, no other properties, and at
+    /// most one incoming reference. Malformed chains are left untouched.
+    /// rdf:nil in subject or object position becomes the empty list.
+    pub fn collapse_lists(&self) -> Formula {
+        let rdf_first = Term::Iri(NamedNode::new_unchecked(vocab::RDF_FIRST));
+        let rdf_rest = Term::Iri(NamedNode::new_unchecked(vocab::RDF_REST));
+
+        // Gather the shape of every blank node in the formula
+        let mut shapes: HashMap<&BlankNode, ListNodeShape> = HashMap::new();
+        for statement in &self.statements {
+            if let Term::BlankNode(node) = &statement.subject {
+                let shape = shapes.entry(node).or_default();
+                if statement.predicate == rdf_first {
+                    shape.first.push(&statement.object);
+                } else if statement.predicate == rdf_rest {
+                    shape.rest.push(&statement.object);
+                } else {
+                    shape.other_properties += 1;
+                }
+            }
+            if let Term::BlankNode(node) = &statement.predicate {
+                shapes.entry(node).or_default().other_properties += 1;
+            }
+            if let Term::BlankNode(node) = &statement.object {
+                shapes.entry(node).or_default().references += 1;
+            }
+        }
+
+        // Build every list whose chain is well-formed, remembering the nodes it consumes
+        let mut lists: HashMap<BlankNode, Term> = HashMap::new();
+        let mut consumed: HashSet<BlankNode> = HashSet::new();
+        for &head in shapes.keys() {
+            let mut items = Vec::new();
+            let mut chain = Vec::new();
+            let mut current = head;
+            let well_formed = loop {
+                let shape = &shapes[current];
+                if shape.first.len() != 1
+                    || shape.rest.len() != 1
+                    || shape.other_properties != 0
+                    || shape.references > 1
+                    || chain.contains(&current)
+                {
+                    break false;
+                }
+                chain.push(current);
+                items.push(shape.first[0].clone());
+                match shape.rest[0] {
+                    Term::Iri(iri) if iri.as_str() == vocab::RDF_NIL => break true,
+                    Term::BlankNode(next) => current = next,
+                    _ => break false,
+                }
+            };
+
+            // A list that contains one of its own nodes cannot be written as a term
+            let self_referencing = items
+                .iter()
+                .any(|item| matches!(item, Term::BlankNode(node) if chain.contains(&node)));
+
+            if well_formed && !self_referencing {
+                consumed.extend(chain.iter().skip(1).map(|&node| node.clone()));
+                lists.insert(head.clone(), Term::List(items));
+            }
+        }
+
+        // Only heads that are not themselves the tail of another list stand for a list
+        lists.retain(|node, _| !consumed.contains(node));
+        consumed.extend(lists.keys().cloned());
+
+        let mut collapsed = Formula {
+            statements: TripleStore::new(),
+            universal_vars: self.universal_vars.clone(),
+            existential_vars: self.existential_vars.clone(),
+        };
+        for statement in &self.statements {
+            if let Term::BlankNode(node) = &statement.subject {
+                if consumed.contains(node) {
+                    continue;
+                }
+            }
+            let predicate = match &statement.predicate {
+                Term::Iri(_) => statement.predicate.clone(),
+                other => collapse_list_term(other, &lists, &mut Vec::new()),
+            };
+            collapsed.add_statement(Statement {
+                subject: collapse_list_term(&statement.subject, &lists, &mut Vec::new()),
+                predicate,
+                object: collapse_list_term(&statement.object, &lists, &mut Vec::new()),
+            });
+        }
+
+        collapsed
+    }
+}
+
+/// How a blank node is used, to decide whether it is part of an RDF list
+#[derive(Default)]
+struct ListNodeShape<'a> {
+    first: Vec<&'a Term>,
+    rest: Vec<&'a Term>,
+    other_properties: usize,
+    references: usize,
+}
+
+/// Replace a list term by its rdf:first/rdf:rest encoding, adding the encoding to `formula`
+fn expand_list_term(term: &Term, formula: &mut Formula) -> Term {
+    match term {
+        Term::List(items) => {
+            let mut head = Term::Iri(NamedNode::new_unchecked(vocab::RDF_NIL));
+            for item in items.iter().rev() {
+                let item = expand_list_term(item, formula);
+                let node = Term::BlankNode(BlankNode::default());
+                formula.add_statement(Statement {
+                    subject: node.clone(),
+                    predicate: Term::Iri(NamedNode::new_unchecked(vocab::RDF_FIRST)),
+                    object: item,
+                });
+                formula.add_statement(Statement {
+                    subject: node.clone(),
+                    predicate: Term::Iri(NamedNode::new_unchecked(vocab::RDF_REST)),
+                    object: head,
+                });
+                head = node;
+            }
+            head
+        }
+        Term::Formula(nested) => Term::Formula(Arc::new(nested.expand_lists())),
+        other => other.clone(),
+    }
+}
+
+/// Replace list heads by the collapsed list terms, recursing into list items and formulas
+///
+/// `visiting` holds the heads currently being expanded so that lists which
+/// contain each other are not expanded forever.
+fn collapse_list_term(term: &Term, lists: &HashMap<BlankNode, Term>, visiting: &mut Vec<BlankNode>) -> Term {
+    match term {
+        Term::BlankNode(node) => match lists.get(node) {
+            Some(list) if !visiting.contains(node) => {
+                visiting.push(node.clone());
+                let collapsed = collapse_list_term(list, lists, visiting);
+                visiting.pop();
+                collapsed
+            }
+            _ => term.clone(),
+        },
+        Term::Iri(iri) if iri.as_str() == vocab::RDF_NIL => Term::List(Vec::new()),
+        Term::List(items) => Term::List(
+            items.iter().map(|item| collapse_list_term(item, lists, visiting)).collect(),
+        ),
+        Term::Formula(nested) => Term::Formula(Arc::new(nested.collapse_lists())),
+        other => other.clone(),
+    }
+}
+
+impl PartialEq for Formula {
+    fn eq(&self, other: &Self) -> bool {
+        if self.universal_vars != other.universal_vars || self.existential_vars != other.existential_vars {
+            return false;
+        }
+
+        self.statements == other.statements
+    }
+}
+
+impl Eq for Formula {}
+
+impl Hash for Formula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Combine per-statement hashes with a commutative sum so order does not matter
+        let mut combined = 0u64;
+        for statement in &self.statements {
+            let mut hasher = DefaultHasher::new();
+            statement.subject.structural_hash(&mut hasher);
+            statement.predicate.structural_hash(&mut hasher);
+            statement.object.structural_hash(&mut hasher);
+            combined = combined.wrapping_add(hasher.finish());
+        }
+
+        combined.hash(state);
+        self.universal_vars.len().hash(state);
+        self.existential_vars.len().hash(state);
+    }
 }
 
 impl Default for Formula {
@@ -120,23 +387,23 @@ impl Default for Formula {
 }
 
 /// Represents an RDF graph (a set of triples)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Graph {
     /// The statements in the graph
-    pub statements: Vec<Statement>,
+    pub statements: TripleStore,
 }
 
 impl Graph {
     /// Creates a new empty graph
     pub fn new() -> Self {
         Graph {
-            statements: Vec::new(),
+            statements: TripleStore::new(),
         }
     }
     
-    /// Adds a statement to the graph
+    /// Adds a statement to the graph, ignoring duplicates
     pub fn add_statement(&mut self, statement: Statement) {
-        self.statements.push(statement);
+        self.statements.insert(statement);
     }
     
     /// Converts an OxRDF triple to an N3 statement
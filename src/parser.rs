@@ -1,20 +1,29 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::Read;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use oxiri::Iri;
-use oxrdf::{NamedNode, BlankNode, Literal};
+use oxiri::{Iri, IriRef};
+use oxrdf::{BlankNode, Literal, NamedNode};
 
-use crate::error::{Error, Result};
+use crate::error::{Error, ParseError, Result};
+use crate::lexer::{Lexer, Position, Token};
 use crate::model::{Formula, Statement, Term};
+use crate::vocab;
 
 /// Options for parsing N3 data
 #[derive(Debug, Clone)]
 pub struct ParseOptions {
     /// Whether to allow N3-specific features (like nested formulas)
     pub allow_n3_extensions: bool,
-    
+
     /// Base IRI to resolve relative IRIs against
+    ///
+    /// `@base` and `BASE` directives replace it for the rest of the document.
     pub base_iri: Option<String>,
+
+    /// Name of the source file, reported in the location of parse errors
+    pub file_name: Option<String>,
 }
 
 impl Default for ParseOptions {
@@ -22,37 +31,34 @@ impl Default for ParseOptions {
         ParseOptions {
             allow_n3_extensions: true,
             base_iri: None,
+            file_name: None,
         }
     }
 }
 
-/// Parse N3 data from a string and return a Formula
+/// Parse N3 data from a reader and return a Formula
 ///
-/// Note: This is a simplified implementation that will be expanded
-/// in the future to support full N3 syntax.
-pub fn parse_n3<R: Read>(_input: R, _options: ParseOptions) -> Result<Formula> {
-    // This is a placeholder implementation
-    // In a real implementation, we would parse the input and convert it to a Formula
-    
-    // For now, we just return an empty formula
-    let mut formula = Formula::new();
-    
-    // TODO: Implement actual N3 parsing
-    // This will involve:
-    // 1. Tokenizing the input
-    // 2. Parsing the tokens into a syntax tree
-    // 3. Converting the syntax tree to a Formula
-    
-    Ok(formula)
+/// The returned formula contains every top-level statement of the document.
+/// Rules written with `=>` and `<=` appear as statements whose subject and
+/// object are quoted formulas.
+pub fn parse_n3<R: Read>(input: R, options: ParseOptions) -> Result<Formula> {
+    N3Parser::new(options)?.parse(input)
 }
 
-/// A placeholder for a more comprehensive N3 parser implementation
-/// 
-/// This will eventually handle N3-specific features like:
-/// - Nested formulas
-/// - Variables and quantification
-/// - Rules with implies (=>)
-/// - Contexts and quoting
+/// A parser for the Notation3 language as described in the W3C Team Submission
+///
+/// Supported syntax includes:
+/// - `@prefix`/`@base` directives and their SPARQL-style `PREFIX`/`BASE` forms
+/// - Nested formulas `{ ... }` and `?x` quick variables
+/// - `@forAll`/`@forSome` quantification
+/// - The `a`, `=`, `=>`, `<=`, `has` and `is ... of` verbs
+/// - Blank node property lists `[ ... ]` and collections `( ... )`, which
+///   become [`Term::List`] terms
+/// - Path expressions with `!` and `^`
+/// - String, numeric and boolean literals with language tags and datatypes
+///
+/// `<=` is read as `log:isImpliedBy`, so backward rules stay distinguishable
+/// from forward rules.
 pub struct N3Parser {
     base_iri: Option<Iri<String>>,
     options: ParseOptions,
@@ -63,18 +69,710 @@ impl N3Parser {
     pub fn new(options: ParseOptions) -> Result<Self> {
         let base_iri = match &options.base_iri {
             Some(iri_str) => Some(Iri::from_str(iri_str)
-                .map_err(|e| Error::ParseError(format!("Invalid base IRI: {}", e)))?),
+                .map_err(|e| ParseError::new(format!("Invalid base IRI: {}", e)).with_token(iri_str.as_str()))?),
             None => None,
         };
-        
+
         Ok(N3Parser {
             base_iri,
             options,
         })
     }
-    
+
+    /// Get the options this parser was created with
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
     /// Parse an N3 document into a Formula
+    ///
+    /// When `allow_n3_extensions` is false only Turtle 1.1 syntax is accepted
+    /// and any N3-specific construct is reported as an error.
     pub fn parse<R: Read>(&self, input: R) -> Result<Formula> {
-        parse_n3(input, self.options.clone())
+        DocumentParser::new(input, self, false)
+            .and_then(DocumentParser::parse_document)
+            .map(|(formula, _)| formula)
+            .map_err(|error| locate(error, self.options.file_name.as_deref()))
+    }
+
+    /// Parse an N3 document, collecting syntax errors instead of stopping at the first one
+    ///
+    /// After a syntax error the parser skips ahead to the end of the statement
+    /// (the next '.' or the '}' closing the enclosing formula) and carries on.
+    /// The returned formula holds every statement that could be parsed. Only
+    /// I/O errors are returned as `Err`.
+    pub fn parse_with_recovery<R: Read>(&self, input: R) -> Result<(Formula, Vec<ParseError>)> {
+        let (formula, diagnostics) = DocumentParser::new(input, self, true)?.parse_document()?;
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|error| match &self.options.file_name {
+                Some(file) => error.in_file(file),
+                None => error,
+            })
+            .collect();
+
+        Ok((formula, diagnostics))
+    }
+
+    /// Parse an N3 document incrementally, yielding top-level statements as they are read
+    ///
+    /// Only a small window of the input and the statements of the current
+    /// top-level block are held in memory at any time, besides the prefixes,
+    /// the base and the names of document-level variables, which makes this
+    /// suitable for loading very large fact files. Blank node labels are not
+    /// remembered: `_:x` gives the same blank node throughout the document. Rules are yielded as
+    /// ordinary statements with quoted formulas as subject and object. The
+    /// iterator stops after the first error.
+    pub fn statements<R: Read>(&self, input: R) -> Result<StatementIter<R>> {
+        let document = DocumentParser::new(input, self, false)
+            .map_err(|error| locate(error, self.options.file_name.as_deref()))?;
+
+        Ok(StatementIter {
+            document,
+            pending: VecDeque::new(),
+            file_name: self.options.file_name.clone(),
+            finished: false,
+        })
+    }
+}
+
+/// Attach a file name to the location of a parse error
+fn locate(error: Error, file: Option<&str>) -> Error {
+    match (error, file) {
+        (Error::ParseError(error), Some(file)) => Error::ParseError(error.in_file(file)),
+        (error, _) => error,
+    }
+}
+
+/// Pull-based iterator over the top-level statements of an N3 document
+///
+/// Created by [`N3Parser::statements`].
+pub struct StatementIter<R: Read> {
+    document: DocumentParser<R>,
+
+    /// Statements produced by the last parsed block that have not been yielded yet
+    pending: VecDeque<Statement>,
+
+    file_name: Option<String>,
+    finished: bool,
+}
+
+impl<R: Read> StatementIter<R> {
+    /// Variables universally quantified at document level by the statements read so far
+    pub fn universal_vars(&self) -> &HashSet<String> {
+        &self.document.scopes[0].formula.universal_vars
+    }
+
+    /// Variables existentially quantified at document level by the statements read so far
+    pub fn existential_vars(&self) -> &HashSet<String> {
+        &self.document.scopes[0].formula.existential_vars
+    }
+}
+
+impl<R: Read> Iterator for StatementIter<R> {
+    type Item = Result<Statement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(statement) = self.pending.pop_front() {
+                return Some(Ok(statement));
+            }
+            if self.finished || self.document.token == Token::Eof {
+                self.finished = true;
+                return None;
+            }
+
+            if let Err(error) = self.document.parse_statement() {
+                self.finished = true;
+                return Some(Err(locate(error, self.file_name.as_deref())));
+            }
+
+            let document = &mut self.document.scopes[0].formula;
+            self.pending.extend(std::mem::take(&mut document.statements));
+        }
+    }
+}
+
+/// A formula under construction together with the variables declared in it
+struct Scope {
+    formula: Formula,
+
+    /// IRIs declared with `@forAll` or `@forSome` in this scope
+    declared_vars: HashSet<String>,
+}
+
+impl Scope {
+    fn new() -> Self {
+        Scope {
+            formula: Formula::new(),
+            declared_vars: HashSet::new(),
+        }
+    }
+}
+
+/// Recursive descent parser state for a single document
+///
+/// Statements are added to the innermost scope as soon as they are complete,
+/// so the document scope can be drained after every top-level statement.
+struct DocumentParser<R: Read> {
+    lexer: Lexer<R>,
+
+    /// The current lookahead token and where it starts
+    token: Token,
+    position: Position,
+
+    base: Option<Iri<String>>,
+    allow_n3_extensions: bool,
+    prefixes: HashMap<String, String>,
+    blank_node_count: usize,
+
+    /// Stack of formulas being built; the first entry is the document itself
+    scopes: Vec<Scope>,
+
+    /// Syntax errors collected so far, when parsing in recovering mode
+    diagnostics: Option<Vec<ParseError>>,
+}
+
+impl<R: Read> DocumentParser<R> {
+    fn new(input: R, parser: &N3Parser, recover: bool) -> Result<Self> {
+        let mut document = DocumentParser {
+            lexer: Lexer::new(input),
+            token: Token::Eof,
+            position: Position::default(),
+            base: parser.base_iri.clone(),
+            allow_n3_extensions: parser.options.allow_n3_extensions,
+            prefixes: HashMap::new(),
+            blank_node_count: 0,
+            scopes: vec![Scope::new()],
+            diagnostics: if recover { Some(Vec::new()) } else { None },
+        };
+
+        while let Err(error) = document.advance() {
+            document.record(error)?;
+        }
+        Ok(document)
+    }
+
+    /// Parse the remaining statements, returning the document and any recovered errors
+    fn parse_document(mut self) -> Result<(Formula, Vec<ParseError>)> {
+        while self.token != Token::Eof {
+            self.parse_statement_or_recover()?;
+        }
+
+        let document = self.scopes.pop().expect("the document scope is never popped");
+        Ok((document.formula, self.diagnostics.unwrap_or_default()))
+    }
+
+    /// Parse a statement, skipping to the next statement boundary on syntax errors when recovering
+    fn parse_statement_or_recover(&mut self) -> Result<()> {
+        let depth = self.scopes.len();
+        match self.parse_statement() {
+            Err(error) if self.diagnostics.is_some() => {
+                self.record(error)?;
+                self.scopes.truncate(depth);
+                self.skip_to_statement_end()
+            }
+            result => result,
+        }
+    }
+
+    /// Skip tokens up to and including the next '.' at this nesting level,
+    /// or up to the '}' closing the current formula
+    fn skip_to_statement_end(&mut self) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.token {
+                Token::Eof => return Ok(()),
+                Token::Dot if depth == 0 => break,
+                Token::CloseBrace if depth == 0 && self.scopes.len() > 1 => return Ok(()),
+                Token::CloseBrace if depth == 0 => break,
+                Token::CloseBrace => depth -= 1,
+                Token::OpenBrace => depth += 1,
+                _ => {}
+            }
+            if let Err(error) = self.advance() {
+                self.record(error)?;
+            }
+        }
+
+        while let Err(error) = self.advance() {
+            self.record(error)?;
+        }
+        Ok(())
+    }
+
+    /// Keep a syntax error as a diagnostic when recovering, otherwise fail with it
+    fn record(&mut self, error: Error) -> Result<()> {
+        match (error, &mut self.diagnostics) {
+            (Error::ParseError(error), Some(diagnostics)) => {
+                diagnostics.push(error);
+                Ok(())
+            }
+            (error, _) => Err(error),
+        }
+    }
+
+    /// Parse a directive, quantification or triples block including its terminator
+    fn parse_statement(&mut self) -> Result<()> {
+        match &self.token {
+            Token::AtKeyword(word) if word == "prefix" => {
+                self.advance()?;
+                self.parse_prefix_declaration()?;
+                self.expect_terminator()
+            }
+            Token::AtKeyword(word) if word == "base" => {
+                self.advance()?;
+                self.parse_base_declaration()?;
+                self.expect_terminator()
+            }
+            Token::Bareword(word) if word.eq_ignore_ascii_case("prefix") => {
+                self.advance()?;
+                self.parse_prefix_declaration()
+            }
+            Token::Bareword(word) if word.eq_ignore_ascii_case("base") => {
+                self.advance()?;
+                self.parse_base_declaration()
+            }
+            Token::AtKeyword(word) if word == "forAll" || word == "forSome" => {
+                let universal = word == "forAll";
+                self.require_n3(self.position, if universal { "@forAll" } else { "@forSome" })?;
+                self.advance()?;
+                self.parse_quantification(universal)?;
+                self.expect_terminator()
+            }
+            Token::AtKeyword(word) if word == "keywords" => {
+                Err(self.error_at(self.position, "@keywords is not supported"))
+            }
+            _ => {
+                let position = self.position;
+                let is_property_list = self.token == Token::OpenBracket;
+                let subject = self.parse_expression()?;
+
+                if !self.allow_n3_extensions {
+                    if let Term::Literal(_) = subject {
+                        return Err(self.error_at(position, "Literals cannot be used as subjects in Turtle"));
+                    }
+                    if !is_property_list && self.token == Token::Dot {
+                        return Err(self.unexpected("a predicate"));
+                    }
+                }
+
+                self.parse_property_list(&subject)?;
+                self.expect_terminator()
+            }
+        }
+    }
+
+    /// Statements end with '.', which may be omitted before the closing brace of a formula
+    fn expect_terminator(&mut self) -> Result<()> {
+        match self.token {
+            Token::Dot => {
+                self.advance()?;
+                Ok(())
+            }
+            Token::CloseBrace if self.scopes.len() > 1 => Ok(()),
+            _ => Err(self.unexpected("'.'")),
+        }
+    }
+
+    fn parse_prefix_declaration(&mut self) -> Result<()> {
+        let prefix = match &self.token {
+            Token::PrefixedName(prefix, local) if local.is_empty() => prefix.clone(),
+            _ => return Err(self.unexpected("a prefix name such as 'ex:'")),
+        };
+        self.advance()?;
+
+        let namespace = self.parse_iri_ref("an IRI reference after the prefix name")?;
+        self.prefixes.insert(prefix, namespace.into_string());
+        Ok(())
     }
-} 
\ No newline at end of file
+
+    fn parse_base_declaration(&mut self) -> Result<()> {
+        // A relative base is itself resolved against the current base
+        let base = self.parse_iri_ref("an IRI reference after the base directive")?;
+        self.base = Some(Iri::parse_unchecked(base.into_string()));
+        Ok(())
+    }
+
+    /// Parse the list of terms following `@forAll` or `@forSome`
+    fn parse_quantification(&mut self, universal: bool) -> Result<()> {
+        loop {
+            let iri = self.parse_iri("an IRI to quantify over")?;
+
+            let scope = self.scopes.last_mut().expect("there is always a current scope");
+            if universal {
+                scope.formula.add_universal_var(iri.as_str());
+            } else {
+                scope.formula.add_existential_var(iri.as_str());
+            }
+            scope.declared_vars.insert(iri.into_string());
+
+            if self.token == Token::Comma {
+                self.advance()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parse `verb objectList (';' verb objectList)*`, which may be empty
+    fn parse_property_list(&mut self, subject: &Term) -> Result<()> {
+        loop {
+            if matches!(
+                self.token,
+                Token::Dot | Token::CloseBrace | Token::CloseBracket | Token::Eof
+            ) {
+                return Ok(());
+            }
+
+            let (verb, inverse) = self.parse_verb()?;
+            loop {
+                let object = self.parse_expression()?;
+                if inverse {
+                    self.emit(object, verb.clone(), subject.clone());
+                } else {
+                    self.emit(subject.clone(), verb.clone(), object);
+                }
+
+                if self.token == Token::Comma {
+                    self.advance()?;
+                } else {
+                    break;
+                }
+            }
+
+            if self.token != Token::Semicolon {
+                return Ok(());
+            }
+            while self.token == Token::Semicolon {
+                self.advance()?;
+            }
+        }
+    }
+
+    /// Parse a verb, returning the predicate and whether subject and object are swapped
+    fn parse_verb(&mut self) -> Result<(Term, bool)> {
+        let position = self.position;
+        let keyword = match &self.token {
+            Token::Bareword(word) | Token::AtKeyword(word) => Some(word.clone()),
+            _ => None,
+        };
+
+        match (keyword.as_deref(), &self.token) {
+            (Some("a"), token) => {
+                if let Token::AtKeyword(_) = token {
+                    self.require_n3(position, "@a")?;
+                }
+                self.advance()?;
+                Ok((iri_term(vocab::RDF_TYPE), false))
+            }
+            (Some("has"), _) => {
+                self.require_n3(position, "has")?;
+                self.advance()?;
+                Ok((self.parse_expression()?, false))
+            }
+            (Some("is"), _) => {
+                self.require_n3(position, "is ... of")?;
+                self.advance()?;
+                let predicate = self.parse_expression()?;
+                match &self.token {
+                    Token::Bareword(word) | Token::AtKeyword(word) if word == "of" => {
+                        self.advance()?;
+                        Ok((predicate, true))
+                    }
+                    _ => Err(self.unexpected("'of'")),
+                }
+            }
+            (_, Token::Equals) => {
+                self.require_n3(position, "'='")?;
+                self.advance()?;
+                Ok((iri_term(vocab::OWL_SAME_AS), false))
+            }
+            (_, Token::Implies) => {
+                self.require_n3(position, "'=>' rules")?;
+                self.advance()?;
+                Ok((iri_term(vocab::LOG_IMPLIES), false))
+            }
+            (_, Token::ImpliedBy) => {
+                self.require_n3(position, "'<=' rules")?;
+                self.advance()?;
+                Ok((iri_term(vocab::LOG_IS_IMPLIED_BY), false))
+            }
+            _ => {
+                let predicate = self.parse_expression()?;
+                if !self.allow_n3_extensions && !matches!(predicate, Term::Iri(_)) {
+                    return Err(self.error_at(position, "Predicates must be IRIs in Turtle"));
+                }
+                Ok((predicate, false))
+            }
+        }
+    }
+
+    /// Parse a path item followed by any number of `!` or `^` path steps
+    fn parse_expression(&mut self) -> Result<Term> {
+        let mut term = self.parse_path_item()?;
+
+        loop {
+            let forward = match self.token {
+                Token::Bang => true,
+                Token::Caret => false,
+                _ => return Ok(term),
+            };
+            self.require_n3(self.position, "path expressions")?;
+            self.advance()?;
+
+            let predicate = self.parse_path_item()?;
+            let node = self.fresh_blank_node();
+            if forward {
+                self.emit(term, predicate, node.clone());
+            } else {
+                self.emit(node.clone(), predicate, term);
+            }
+            term = node;
+        }
+    }
+
+    fn parse_path_item(&mut self) -> Result<Term> {
+        let position = self.position;
+
+        // Leave punctuation in place so error recovery can resynchronise on it
+        if matches!(
+            self.token,
+            Token::Dot
+                | Token::Semicolon
+                | Token::Comma
+                | Token::CloseBracket
+                | Token::CloseParen
+                | Token::CloseBrace
+                | Token::Eof
+        ) {
+            return Err(self.unexpected("a term"));
+        }
+
+        match self.advance()? {
+            Token::IriRef(iri) => {
+                let node = self.resolve_iri(&iri, position)?;
+                Ok(self.named_term(node))
+            }
+            Token::PrefixedName(prefix, local) => {
+                let node = self.expand_prefixed_name(&prefix, &local, position)?;
+                Ok(self.named_term(node))
+            }
+            Token::BlankNodeLabel(label) => Ok(self.labelled_blank_node(label)),
+            Token::Variable(name) => {
+                self.require_n3(position, "variables")?;
+                // Quick variables are universally quantified in the parent formula
+                let parent = self.scopes.len().saturating_sub(2);
+                self.scopes[parent].formula.add_universal_var(&name);
+                Ok(Term::Variable(name))
+            }
+            Token::String { value, language } => self.parse_literal(value, language, position),
+            Token::Integer(value) => Ok(typed_literal(value, vocab::XSD_INTEGER)),
+            Token::Decimal(value) => Ok(typed_literal(value, vocab::XSD_DECIMAL)),
+            Token::Double(value) => Ok(typed_literal(value, vocab::XSD_DOUBLE)),
+            Token::Bareword(word) if word == "true" || word == "false" => {
+                Ok(typed_literal(word, vocab::XSD_BOOLEAN))
+            }
+            Token::AtKeyword(word) if word == "true" || word == "false" => {
+                self.require_n3(position, "@true and @false")?;
+                Ok(typed_literal(word, vocab::XSD_BOOLEAN))
+            }
+            Token::OpenBracket => {
+                let node = self.fresh_blank_node();
+                self.parse_property_list(&node)?;
+                self.expect(Token::CloseBracket)?;
+                Ok(node)
+            }
+            Token::OpenParen => {
+                let mut items = Vec::new();
+                while self.token != Token::CloseParen {
+                    if self.token == Token::Eof {
+                        return Err(self.error_at(position, "Unterminated collection"));
+                    }
+                    items.push(self.parse_expression()?);
+                }
+                self.advance()?;
+                Ok(Term::List(items))
+            }
+            Token::OpenBrace => {
+                self.require_n3(position, "quoted formulas")?;
+                self.scopes.push(Scope::new());
+                while self.token != Token::CloseBrace {
+                    if self.token == Token::Eof {
+                        return Err(self.error_at(position, "Unterminated formula"));
+                    }
+                    self.parse_statement_or_recover()?;
+                }
+                self.advance()?;
+
+                let scope = self.scopes.pop().expect("the formula scope was just pushed");
+                Ok(Term::Formula(Arc::new(scope.formula)))
+            }
+            token => Err(ParseError::new(format!("Expected a term but found '{}'", token))
+                .at(position.into())
+                .with_token(token.to_string())
+                .into()),
+        }
+    }
+
+    fn parse_literal(&mut self, value: String, language: Option<String>, position: Position) -> Result<Term> {
+        if let Some(language) = language {
+            let literal = Literal::new_language_tagged_literal(value, language)
+                .map_err(|e| self.error_at(position, &format!("Invalid language tag: {}", e)))?;
+            return Ok(Term::Literal(literal));
+        }
+
+        if self.token != Token::DoubleCaret {
+            return Ok(Term::Literal(Literal::new_simple_literal(value)));
+        }
+        self.advance()?;
+
+        let datatype = self.parse_iri("a datatype IRI after '^^'")?;
+
+        Ok(Term::Literal(Literal::new_typed_literal(value, datatype)))
+    }
+
+    /// Consume the current token if it is an IRI reference
+    fn parse_iri_ref(&mut self, expected: &str) -> Result<NamedNode> {
+        let node = match &self.token {
+            Token::IriRef(iri) => self.resolve_iri(iri, self.position)?,
+            _ => return Err(self.unexpected(expected)),
+        };
+        self.advance()?;
+        Ok(node)
+    }
+
+    /// Consume the current token if it is an IRI reference or a prefixed name
+    fn parse_iri(&mut self, expected: &str) -> Result<NamedNode> {
+        let node = match &self.token {
+            Token::IriRef(iri) => self.resolve_iri(iri, self.position)?,
+            Token::PrefixedName(prefix, local) => self.expand_prefixed_name(prefix, local, self.position)?,
+            _ => return Err(self.unexpected(expected)),
+        };
+        self.advance()?;
+        Ok(node)
+    }
+
+    /// Reject an N3-only construct when parsing in Turtle compatibility mode
+    fn require_n3(&self, position: Position, feature: &str) -> Result<()> {
+        if self.allow_n3_extensions {
+            Ok(())
+        } else {
+            Err(self.error_at(
+                position,
+                &format!("N3 extension not allowed in Turtle: {}", feature),
+            ))
+        }
+    }
+
+    /// Turn an IRI into a term, honouring variables declared in enclosing scopes
+    fn named_term(&self, node: NamedNode) -> Term {
+        if self.scopes.iter().any(|scope| scope.declared_vars.contains(node.as_str())) {
+            Term::Variable(node.into_string())
+        } else {
+            Term::Iri(node)
+        }
+    }
+
+    /// Resolve an IRI reference against the current base following RFC 3986
+    fn resolve_iri(&self, iri: &str, position: Position) -> Result<NamedNode> {
+        let token = format!("<{}>", iri);
+        let invalid = |e| self.token_error_at(position, &token, &format!("Invalid IRI {}: {}", token, e));
+
+        let reference = IriRef::parse(iri).map_err(invalid)?;
+        let resolved = match &self.base {
+            Some(base) => base.resolve(iri).map_err(invalid)?.into_inner(),
+            None if reference.is_absolute() => reference.into_inner().to_string(),
+            None => {
+                return Err(self.token_error_at(
+                    position,
+                    &token,
+                    &format!("Relative IRI {} cannot be resolved without a base IRI", token),
+                ));
+            }
+        };
+
+        Ok(NamedNode::new_unchecked(resolved))
+    }
+
+    fn expand_prefixed_name(&self, prefix: &str, local: &str, position: Position) -> Result<NamedNode> {
+        let token = format!("{}:{}", prefix, local);
+        let namespace = self.prefixes.get(prefix).ok_or_else(|| {
+            self.token_error_at(position, &token, &format!("Undefined prefix '{}:'", prefix))
+        })?;
+
+        NamedNode::new(format!("{}{}", namespace, local))
+            .map_err(|e| self.token_error_at(position, &token, &format!("Invalid IRI for '{}': {}", token, e)))
+    }
+
+    /// The blank node for `_:label`, the same wherever the label appears in the document
+    ///
+    /// Its id is derived from the label, rather than looked up, so streaming
+    /// parsers need not remember every label they have seen. The `l` prefix
+    /// keeps it apart from the `b0`, `b1`, ... ids of unlabelled blank nodes.
+    fn labelled_blank_node(&self, label: String) -> Term {
+        Term::BlankNode(BlankNode::new_unchecked(format!("l{}", label)))
+    }
+
+    fn fresh_blank_node(&mut self) -> Term {
+        Term::BlankNode(self.next_blank_node())
+    }
+
+    /// Blank nodes get document-unique identifiers so labels never clash with anonymous nodes
+    fn next_blank_node(&mut self) -> BlankNode {
+        let id = format!("b{}", self.blank_node_count);
+        self.blank_node_count += 1;
+        BlankNode::new_unchecked(id)
+    }
+
+    /// Add a statement to the innermost formula being parsed
+    fn emit(&mut self, subject: Term, predicate: Term, object: Term) {
+        let scope = self.scopes.last_mut().expect("there is always a current scope");
+        scope.formula.add_statement(Statement {
+            subject,
+            predicate,
+            object,
+        });
+    }
+
+    /// Move to the next token, returning the one that was current
+    fn advance(&mut self) -> Result<Token> {
+        let (token, position) = self.lexer.next_token()?;
+        self.position = position;
+        Ok(std::mem::replace(&mut self.token, token))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        if self.token == expected {
+            self.advance()?;
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", expected)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        let message = format!("Expected {} but found '{}'", expected, self.token);
+        if self.token == Token::Eof {
+            self.error_at(self.position, &message)
+        } else {
+            self.token_error_at(self.position, &self.token.to_string(), &message)
+        }
+    }
+
+    fn error_at(&self, position: Position, message: &str) -> Error {
+        ParseError::new(message).at(position.into()).into()
+    }
+
+    fn token_error_at(&self, position: Position, token: &str, message: &str) -> Error {
+        ParseError::new(message).at(position.into()).with_token(token).into()
+    }
+}
+
+fn iri_term(iri: &str) -> Term {
+    Term::Iri(NamedNode::new_unchecked(iri))
+}
+
+fn typed_literal(value: String, datatype: &str) -> Term {
+    Term::Literal(Literal::new_typed_literal(value, NamedNode::new_unchecked(datatype)))
+}
@@ -0,0 +1,172 @@
+use oxrdf::BlankNode;
+
+use crate::model::{Formula, Statement, Term};
+use crate::reasoner::{Rule, RuleDirection};
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// The facts, rules and queries of a parsed N3 document
+///
+/// Built with [`Program::from_formula`] from the formula returned by
+/// [`parse_n3`](crate::parse_n3) and loaded into an engine with
+/// [`ProofEngine::add_program`](crate::ProofEngine::add_program).
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    /// Top-level statements that are neither rules nor queries
+    pub facts: Formula,
+
+    /// Rules written `{ P } => { C }`, i.e. with `log:implies`
+    pub forward_rules: Vec<Rule>,
+
+    /// Rules written `{ C } <= { P }`, i.e. with `log:isImpliedBy`
+    pub backward_rules: Vec<Rule>,
+
+    /// Queries written `{ P } log:query { C }`, whose conclusion is the template of each answer
+    pub queries: Vec<Rule>,
+}
+
+impl Program {
+    /// Splits the top-level statements of a document into facts, rules and queries
+    ///
+    /// A statement is a rule or query when its predicate is `log:implies`,
+    /// `log:isImpliedBy` or `log:query` and both sides are formulas; `true`
+    /// also stands for an empty premise, as in `true => { ... }`. Rules are
+    /// named `rule1`, `rule2`, ... and queries `query1`, ... in document order.
+    /// Statements nested inside other formulas are left alone.
+    ///
+    /// Variables are scoped as in N3: quick variables written directly in a
+    /// premise or conclusion and variables declared with `@forAll` at the top
+    /// level belong to the document, so they are shared by the premise and
+    /// the conclusion of each rule, and so are quick variables of formulas
+    /// nested in them, as in EYE. Variables declared with `@forAll` inside a
+    /// premise or conclusion stay local to it. `@forSome` variables become
+    /// blank nodes, and the same blank node is used for a top-level
+    /// `@forSome` variable wherever it appears.
+    pub fn from_formula(formula: &Formula) -> Program {
+        let document_existentials: Substitution = formula
+            .existential_vars
+            .iter()
+            .map(|var| (var.clone(), Term::BlankNode(BlankNode::default())))
+            .collect();
+
+        let mut program = Program::default();
+        let mut rule_count = 0;
+        for statement in &formula.statements {
+            let Some((kind, premise, conclusion)) = split_rule(statement) else {
+                program.facts.add_statement(document_existentials.apply_statement(statement));
+                continue;
+            };
+
+            let premise = localize(&premise, "premise", &document_existentials);
+            let conclusion = localize(&conclusion, "conclusion", &document_existentials);
+            match kind {
+                RuleKind::Query => {
+                    let name = format!("query{}", program.queries.len() + 1);
+                    program.queries.push(Rule::new(&name, vec![premise], conclusion));
+                }
+                RuleKind::Forward | RuleKind::Backward => {
+                    rule_count += 1;
+                    let name = format!("rule{}", rule_count);
+                    let rule = Rule::new(&name, vec![premise], conclusion);
+                    if kind == RuleKind::Forward {
+                        program.forward_rules.push(rule);
+                    } else {
+                        program.backward_rules.push(rule.with_direction(RuleDirection::Backward));
+                    }
+                }
+            }
+        }
+        program.facts.universal_vars = formula
+            .universal_vars
+            .iter()
+            .filter(|var| program.facts.statements.iter().any(|statement| mentions(statement, var)))
+            .cloned()
+            .collect();
+        program
+    }
+
+    /// Returns every rule, forward rules first
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.forward_rules.iter().chain(&self.backward_rules)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleKind {
+    Forward,
+    Backward,
+    Query,
+}
+
+/// Recognises a rule or query statement, returning its kind, premise and conclusion
+fn split_rule(statement: &Statement) -> Option<(RuleKind, Formula, Formula)> {
+    let Term::Iri(predicate) = &statement.predicate else {
+        return None;
+    };
+    let kind = match predicate.as_str() {
+        vocab::LOG_IMPLIES => RuleKind::Forward,
+        vocab::LOG_IS_IMPLIED_BY => RuleKind::Backward,
+        vocab::LOG_QUERY => RuleKind::Query,
+        _ => return None,
+    };
+
+    let subject = as_formula(&statement.subject)?;
+    let object = as_formula(&statement.object)?;
+    Some(match kind {
+        RuleKind::Backward => (kind, object, subject),
+        _ => (kind, subject, object),
+    })
+}
+
+/// A formula term, or `true` read as the empty formula
+fn as_formula(term: &Term) -> Option<Formula> {
+    match term {
+        Term::Formula(formula) => Some((**formula).clone()),
+        Term::Literal(literal)
+            if literal.value() == "true" && literal.datatype().as_str() == vocab::XSD_BOOLEAN =>
+        {
+            Some(Formula::new())
+        }
+        _ => None,
+    }
+}
+
+/// Resolves the quantifiers of a premise or conclusion
+///
+/// Its own `@forSome` variables become fresh blank nodes and top-level
+/// `@forSome` variables the blank nodes chosen for the document, unless a
+/// variable of the same name is quantified here. Its own `@forAll`
+/// variables, which unlike quick variables are named by IRIs, are renamed
+/// apart, e.g. `http://example.org/x` to `http://example.org/x in premise`,
+/// a name no document variable can have, so they stay distinct from the
+/// rule's variables once the engine matches the rule.
+fn localize(part: &Formula, side: &str, document_existentials: &Substitution) -> Formula {
+    let shadowed = |var: &str| part.universal_vars.contains(var) || part.existential_vars.contains(var);
+    let mut renaming: Substitution = document_existentials
+        .iter()
+        .filter(|(var, _)| !shadowed(var))
+        .map(|(var, term)| (var.to_string(), term.clone()))
+        .collect();
+    for var in &part.existential_vars {
+        renaming.bind(var, Term::BlankNode(BlankNode::default()));
+    }
+    let local = |var: &String| if var.contains(':') { format!("{} in {}", var, side) } else { var.clone() };
+    for var in part.universal_vars.iter().filter(|var| var.contains(':')) {
+        renaming.bind(var, Term::Variable(local(var)));
+    }
+    let mut localized = renaming.apply_formula(part);
+    localized.universal_vars = part.universal_vars.iter().map(local).collect();
+    localized
+}
+
+fn mentions(statement: &Statement, var: &str) -> bool {
+    fn term_mentions(term: &Term, var: &str) -> bool {
+        match term {
+            Term::Variable(name) => name == var,
+            Term::List(items) => items.iter().any(|item| term_mentions(item, var)),
+            Term::Formula(formula) => formula.statements.iter().any(|statement| mentions(statement, var)),
+            _ => false,
+        }
+    }
+    term_mentions(&statement.subject, var) || term_mentions(&statement.predicate, var) || term_mentions(&statement.object, var)
+}
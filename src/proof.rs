@@ -1,8 +1,8 @@
 use std::fmt;
-use std::sync::Arc;
 
 use crate::error::{Error, Result};
-use crate::model::{Formula, Statement, Term};
+use crate::model::{Formula, Statement};
+use crate::reasoner::Rule;
 
 /// Represents a single step in a proof
 #[derive(Debug, Clone)]
@@ -18,6 +18,9 @@ pub struct ProofStep {
     
     /// Optional description of this step
     pub description: Option<String>,
+
+    /// Builtin statements that were evaluated to hold for this step, such as `(1 2) math:sum 3`
+    pub evidence: Vec<Statement>,
 }
 
 impl ProofStep {
@@ -28,6 +31,7 @@ impl ProofStep {
             rule: rule.to_string(),
             premises,
             description: None,
+            evidence: Vec::new(),
         }
     }
     
@@ -36,6 +40,12 @@ impl ProofStep {
         self.description = Some(description.to_string());
         self
     }
+
+    /// Add the builtin statements this step relied on
+    pub fn with_evidence(mut self, evidence: Vec<Statement>) -> Self {
+        self.evidence = evidence;
+        self
+    }
 }
 
 impl fmt::Display for ProofStep {
@@ -99,22 +109,12 @@ impl Proof {
         // Check if the proof establishes its goal
         if let Some(goal) = &self.goal {
             if let Some(last_step) = self.steps.last() {
-                // Compare the last step's conclusion with the goal
-                // This is a simplistic check; a real implementation would do more sophisticated
-                // semantic comparison or entailment checking
-                if last_step.conclusion.statements.len() != goal.statements.len() {
-                    return Ok(false);
-                }
-                
-                // TODO: Implement proper formula entailment check
-                // For now, just a basic equality check
-                for (i, stmt) in last_step.conclusion.statements.iter().enumerate() {
-                    if i >= goal.statements.len() || *stmt != goal.statements[i] {
-                        return Ok(false);
-                    }
-                }
-                
-                Ok(true)
+                // The conclusion establishes the goal if it is the same formula
+                // up to renaming of blank nodes and variables, or if it contains
+                // an instance of the goal's statements
+                let instance_of_goal = Rule::new("goal", vec![goal.clone()], Formula::new())
+                    .can_apply(std::slice::from_ref(&last_step.conclusion));
+                Ok(last_step.conclusion.is_isomorphic(goal) || instance_of_goal)
             } else {
                 // Empty proof can't establish a non-trivial goal
                 Ok(false)
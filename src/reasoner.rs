@@ -1,9 +1,37 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use std::sync::Arc;
 
-use crate::error::{Error, Result};
+use chrono::{DateTime, FixedOffset};
+use oxrdf::BlankNode;
+
+use crate::builtin::{Builtin, BuiltinContext, BuiltinRegistry, KnowledgeBase};
+use crate::builtin_log::scoped_query;
+use crate::builtin_time::{Clock, SystemClock};
+use crate::error::{BuiltinError, Error, Result};
+use crate::interner::{IdTriple, Interner};
 use crate::model::{Formula, Statement, Term};
+use crate::program::Program;
 use crate::proof::{Proof, ProofStep};
+use crate::store::TripleStore;
+use crate::stratification::{stratify, StratificationPolicy};
+use crate::unify::Substitution;
+use crate::vocab;
+
+/// Whether a rule derives new facts or only proves goals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleDirection {
+    /// Fired by saturation and usable by backward chaining, like `=>`
+    #[default]
+    Forward,
+
+    /// Only used by backward chaining to prove goals, like `<=`
+    Backward,
+}
 
 /// Represents a rule that can be used for inference
 #[derive(Debug, Clone)]
@@ -19,6 +47,9 @@ pub struct Rule {
     
     /// Optional human-readable description
     pub description: Option<String>,
+
+    /// Whether saturation fires the rule or only backward chaining uses it
+    pub direction: RuleDirection,
 }
 
 impl Rule {
@@ -29,6 +60,29 @@ impl Rule {
             premises,
             conclusion,
             description: None,
+            direction: RuleDirection::Forward,
+        }
+    }
+
+    /// Create a rule from a `log:implies` or `log:isImpliedBy` statement between two formulas
+    ///
+    /// `{ P } => { C }` gives a forward rule and `{ C } <= { P }` a backward
+    /// one, both with premise `P` and conclusion `C`. Returns `None` for any
+    /// other statement.
+    pub fn from_statement(name: &str, statement: &Statement) -> Option<Rule> {
+        let (Term::Formula(subject), Term::Iri(predicate), Term::Formula(object)) =
+            (&statement.subject, &statement.predicate, &statement.object)
+        else {
+            return None;
+        };
+
+        match predicate.as_str() {
+            vocab::LOG_IMPLIES => Some(Rule::new(name, vec![(**subject).clone()], (**object).clone())),
+            vocab::LOG_IS_IMPLIED_BY => Some(
+                Rule::new(name, vec![(**object).clone()], (**subject).clone())
+                    .with_direction(RuleDirection::Backward),
+            ),
+            _ => None,
         }
     }
     
@@ -37,41 +91,927 @@ impl Rule {
         self.description = Some(desc.to_string());
         self
     }
+
+    /// Set whether the rule is fired forward or only used to prove goals
+    pub fn with_direction(mut self, direction: RuleDirection) -> Self {
+        self.direction = direction;
+        self
+    }
     
     /// Check if this rule can be applied to the given formulas
+    ///
+    /// True when every premise statement matches a statement of the given
+    /// formulas under one consistent binding of the rule's variables. No
+    /// builtins are evaluated: a premise such as `?x math:greaterThan 5` is
+    /// matched like any other statement.
     pub fn can_apply(&self, formulas: &[Formula]) -> bool {
-        if formulas.len() < self.premises.len() {
+        let data: TripleStore = formulas.iter().flat_map(|formula| formula.statements.iter().cloned()).collect();
+        let builtins = BuiltinRegistry::new();
+        let context = BuiltinContext::new(&builtins);
+        let mut found = false;
+        join(&Pattern::over(self.premise_patterns(), 0..data.len()), &data, &context, Substitution::new(), &mut |_| {
+            found = true;
+            false
+        })
+        .expect("builtins are only evaluated by an engine");
+        found
+    }
+
+    /// Find every binding of the rule's variables under which all premises hold in the given formulas
+    ///
+    /// The premises of all premise formulas are matched together against the
+    /// statements of all given formulas. Blank nodes in the premises match
+    /// any term, like variables, and appear in the bindings as `_:label`.
+    /// As with [`Rule::can_apply`], builtins are not evaluated.
+    pub fn matches(&self, formulas: &[Formula]) -> Vec<Substitution> {
+        let builtins = BuiltinRegistry::new();
+        self.matches_in(formulas, &BuiltinContext::new(&builtins))
+            .expect("builtins are only evaluated by an engine")
+    }
+
+    /// Like [`Rule::matches`], evaluating the premises with builtins in `context`
+    fn matches_in(&self, formulas: &[Formula], context: &BuiltinContext<'_>) -> Result<Vec<Substitution>> {
+        let data: TripleStore = formulas.iter().flat_map(|formula| formula.statements.iter().cloned()).collect();
+        let patterns: Vec<Pattern> = self
+            .premise_patterns()
+            .into_iter()
+            .map(|statement| Pattern {
+                builtin: context.builtins().for_predicate(&statement.predicate).cloned(),
+                statement,
+                window: 0..data.len(),
+            })
+            .collect();
+        let mut bindings = Vec::new();
+        join(&patterns, &data, context, Substitution::new(), &mut |binding| {
+            bindings.push(binding);
+            true
+        })?;
+        Ok(bindings)
+    }
+
+    /// Apply the rule to the given formulas
+    ///
+    /// Returns one instantiated conclusion per distinct binding of the
+    /// variables the conclusion uses. Blank nodes in the conclusion are
+    /// replaced by fresh blank nodes in every result. As with
+    /// [`Rule::can_apply`], builtins are not evaluated; use
+    /// [`ProofEngine::apply_rule`] for rules with builtin premises.
+    pub fn apply(&self, formulas: &[Formula]) -> Result<Vec<Formula>> {
+        let conclusions = self.conclusions(self.matches(formulas))?;
+        Ok(conclusions.into_iter().map(|(conclusion, _)| conclusion).collect())
+    }
+
+    /// The distinct conclusions of the given bindings, each with the binding it came from
+    fn conclusions(&self, bindings: Vec<Substitution>) -> Result<Vec<(Formula, Substitution)>> {
+        if bindings.is_empty() {
+            return Err(Error::ReasoningError(format!(
+                "Rule '{}' cannot be applied to the given formulas: no statements match its premises", self.name
+            )));
+        }
+
+        let mut seen = HashSet::new();
+        let mut conclusions = Vec::new();
+        for binding in bindings {
+            let conclusion = binding.apply_formula(&self.conclusion);
+            if seen.insert(conclusion.clone()) {
+                conclusions.push((with_fresh_blank_nodes(&conclusion), binding));
+            }
+        }
+        Ok(conclusions)
+    }
+
+    /// The statements of all premises, with blank nodes turned into variables
+    fn premise_patterns(&self) -> Vec<Statement> {
+        self.premises
+            .iter()
+            .flat_map(|premise| premise.statements.iter())
+            .map(as_pattern)
+            .collect()
+    }
+}
+
+/// A premise statement and the insertion-order window of facts it may match
+///
+/// Statements whose predicate is a builtin are evaluated by it instead of
+/// being matched, and ignore the window.
+#[derive(Clone)]
+struct Pattern {
+    statement: Statement,
+    window: Range<usize>,
+    builtin: Option<Arc<dyn Builtin>>,
+}
+
+impl Pattern {
+    /// Pairs every statement with the same window, without builtins
+    fn over(statements: Vec<Statement>, window: Range<usize>) -> Vec<Pattern> {
+        statements
+            .into_iter()
+            .map(|statement| Pattern { statement, window: window.clone(), builtin: None })
+            .collect()
+    }
+}
+
+/// Statements that rule premises can be matched against
+trait FactSource {
+    /// Statements inside `window` matching a pattern where `None` matches anything
+    fn matching<'a>(
+        &'a self,
+        subject: Option<&Term>,
+        predicate: Option<&Term>,
+        object: Option<&Term>,
+        window: Range<usize>,
+    ) -> Box<dyn Iterator<Item = Cow<'a, Statement>> + 'a>;
+}
+
+impl FactSource for TripleStore {
+    fn matching<'a>(
+        &'a self,
+        subject: Option<&Term>,
+        predicate: Option<&Term>,
+        object: Option<&Term>,
+        window: Range<usize>,
+    ) -> Box<dyn Iterator<Item = Cow<'a, Statement>> + 'a> {
+        Box::new(
+            self.matching_indices(subject, predicate, object)
+                .filter(move |id| window.contains(id))
+                .map(|id| Cow::Borrowed(&self[id])),
+        )
+    }
+}
+
+/// The engine's interned facts, looked up by term and converted back to statements
+struct InternedFacts<'a> {
+    interner: &'a Interner,
+    facts: &'a TripleStore<IdTriple>,
+}
+
+impl FactSource for InternedFacts<'_> {
+    fn matching<'a>(
+        &'a self,
+        subject: Option<&Term>,
+        predicate: Option<&Term>,
+        object: Option<&Term>,
+        window: Range<usize>,
+    ) -> Box<dyn Iterator<Item = Cow<'a, Statement>> + 'a> {
+        // A bound term the interner has never seen cannot match anything
+        let lookup = |term: Option<&Term>| match term {
+            Some(term) => self.interner.get(term).map(Some),
+            None => Some(None),
+        };
+        let (Some(subject), Some(predicate), Some(object)) = (lookup(subject), lookup(predicate), lookup(object)) else {
+            return Box::new(std::iter::empty());
+        };
+
+        let matches: Vec<usize> = self
+            .facts
+            .matching_indices(subject.as_ref(), predicate.as_ref(), object.as_ref())
+            .filter(|id| window.contains(id))
+            .collect();
+        Box::new(
+            matches
+                .into_iter()
+                .map(|id| Cow::Owned(self.interner.resolve_statement(&self.facts[id]))),
+        )
+    }
+}
+
+/// The engine's facts are the knowledge base that scoped builtins such as `log:notIncludes` search
+impl KnowledgeBase for InternedFacts<'_> {
+    fn query(
+        &self,
+        context: &BuiltinContext<'_>,
+        pattern: &Formula,
+        binding: &Substitution,
+    ) -> std::result::Result<Vec<Substitution>, BuiltinError> {
+        query(pattern, self, self.facts.len(), context, binding)
+    }
+}
+
+/// Finds every extension of `binding` under which all `patterns` match statements of `data`
+///
+/// Builtins are evaluated in the order they are written: once the patterns
+/// before them are matched and their arguments are bound enough. A builtin
+/// that never is makes the join fail. Other patterns are matched
+/// most-bound first, using the store's indexes for the bound terms, and
+/// each only against the facts in its window. `on_match` returns false to
+/// stop the search, in which case `join` returns false too.
+fn join(
+    patterns: &[Pattern],
+    data: &dyn FactSource,
+    context: &BuiltinContext<'_>,
+    binding: Substitution,
+    on_match: &mut dyn FnMut(Substitution) -> bool,
+) -> std::result::Result<bool, BuiltinError> {
+    if patterns.is_empty() {
+        return Ok(on_match(binding));
+    }
+
+    for (i, pattern) in patterns.iter().enumerate() {
+        let Some(builtin) = &pattern.builtin else {
+            break;
+        };
+        let Some(extensions) = evaluate_builtin(context, builtin.as_ref(), &pattern.statement, &binding)? else {
+            continue;
+        };
+        let rest = without(patterns, i);
+        for extended in extensions {
+            if !join(&rest, data, context, extended, on_match)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    let instantiated: Vec<Statement> = patterns
+        .iter()
+        .map(|pattern| binding.apply_statement(&pattern.statement))
+        .collect();
+    let Some(next) = (0..instantiated.len())
+        .filter(|&i| patterns[i].builtin.is_none())
+        .max_by_key(|&i| {
+            let pattern = &instantiated[i];
+            // Prefer the earliest pattern among equally bound ones
+            let terms = [&pattern.subject, &pattern.predicate, &pattern.object];
+            (terms.iter().filter(|term| term.is_ground()).count(), std::cmp::Reverse(i))
+        })
+    else {
+        // Only builtins are left, and none of them can be evaluated
+        return Ok(true);
+    };
+
+    let pattern = &instantiated[next];
+    let key = |term: &Term| if term.is_ground() { Some(term.clone()) } else { None };
+    let (subject, predicate, object) = (key(&pattern.subject), key(&pattern.predicate), key(&pattern.object));
+
+    let rest = without(patterns, next);
+    let window = patterns[next].window.clone();
+    for candidate in data.matching(subject.as_ref(), predicate.as_ref(), object.as_ref(), window) {
+        if let Some(extended) = binding.match_statement(&patterns[next].statement, &candidate) {
+            if !join(&rest, data, context, extended, on_match)? {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Evaluates a builtin statement under a binding
+///
+/// Returns `None` if the builtin needs more of its arguments bound.
+fn evaluate_builtin(
+    context: &BuiltinContext<'_>,
+    builtin: &dyn Builtin,
+    statement: &Statement,
+    binding: &Substitution,
+) -> std::result::Result<Option<Vec<Substitution>>, BuiltinError> {
+    let subject = binding.apply_term(&statement.subject);
+    let object = binding.apply_term(&statement.object);
+    match builtin.evaluate_in(context, &subject, &object, binding) {
+        Ok(extensions) => Ok(Some(extensions)),
+        Err(BuiltinError::InsufficientlyBound { .. }) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Finds every extension of `binding` under which the statements of `pattern` hold in `data`
+///
+/// Builtins of the pattern are evaluated in `context`. Its blank nodes
+/// match any term and are left out of the results.
+fn query(
+    pattern: &Formula,
+    data: &dyn FactSource,
+    len: usize,
+    context: &BuiltinContext<'_>,
+    binding: &Substitution,
+) -> std::result::Result<Vec<Substitution>, BuiltinError> {
+    let patterns: Vec<Pattern> = pattern
+        .statements
+        .iter()
+        .map(|statement| Pattern {
+            statement: as_pattern(statement),
+            window: 0..len,
+            builtin: context.builtins().for_predicate(&statement.predicate).cloned(),
+        })
+        .collect();
+    let mut solutions = Vec::new();
+    join(&patterns, data, context, binding.clone(), &mut |found| {
+        let solution: Substitution = found
+            .iter()
+            .filter(|(var, _)| !var.starts_with("_:"))
+            .map(|(var, term)| (var.to_string(), found.apply_term(term)))
+            .collect();
+        if !solutions.contains(&solution) {
+            solutions.push(solution);
+        }
+        true
+    })?;
+    Ok(solutions)
+}
+
+/// Finds every extension of `binding` under which the statements of `pattern` hold in the quoted formula `scope`
+pub(crate) fn query_formula(
+    context: &BuiltinContext<'_>,
+    scope: &Formula,
+    pattern: &Formula,
+    binding: &Substitution,
+) -> std::result::Result<Vec<Substitution>, BuiltinError> {
+    query(pattern, &scope.statements, scope.statements.len(), context, binding)
+}
+
+fn without<T: Clone>(items: &[T], index: usize) -> Vec<T> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != index)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+/// Turns the blank nodes of a statement into variables so it can be matched like a pattern
+fn as_pattern(statement: &Statement) -> Statement {
+    Statement {
+        subject: blank_nodes_as_variables(&statement.subject),
+        predicate: blank_nodes_as_variables(&statement.predicate),
+        object: blank_nodes_as_variables(&statement.object),
+    }
+}
+
+fn blank_nodes_as_variables(term: &Term) -> Term {
+    match term {
+        Term::BlankNode(node) => Term::Variable(format!("_:{}", node.as_str())),
+        Term::List(items) => Term::List(items.iter().map(blank_nodes_as_variables).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Replaces every blank node of a formula by a fresh one, consistently
+fn with_fresh_blank_nodes(formula: &Formula) -> Formula {
+    let mut fresh = HashMap::new();
+    let mut result = Formula::new();
+    for statement in &formula.statements {
+        result.add_statement(Statement {
+            subject: refresh_blank_nodes(&statement.subject, &mut fresh),
+            predicate: refresh_blank_nodes(&statement.predicate, &mut fresh),
+            object: refresh_blank_nodes(&statement.object, &mut fresh),
+        });
+    }
+    result.universal_vars = formula.universal_vars.clone();
+    result.existential_vars = formula.existential_vars.clone();
+    result
+}
+
+fn refresh_blank_nodes(term: &Term, fresh: &mut HashMap<BlankNode, BlankNode>) -> Term {
+    match term {
+        Term::BlankNode(node) => Term::BlankNode(fresh.entry(node.clone()).or_default().clone()),
+        Term::List(items) => Term::List(items.iter().map(|item| refresh_blank_nodes(item, fresh)).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Lists the variables of a statement in order of first occurrence
+fn variables_of(statement: &Statement) -> Vec<String> {
+    let mut variables = Vec::new();
+    collect_variables(&statement.subject, &mut variables);
+    collect_variables(&statement.predicate, &mut variables);
+    collect_variables(&statement.object, &mut variables);
+    variables
+}
+
+fn collect_variables(term: &Term, variables: &mut Vec<String>) {
+    match term {
+        Term::Variable(var) if !variables.contains(var) => variables.push(var.clone()),
+        Term::List(items) => items.iter().for_each(|item| collect_variables(item, variables)),
+        Term::Formula(formula) => {
+            for statement in &formula.statements {
+                collect_variables(&statement.subject, variables);
+                collect_variables(&statement.predicate, variables);
+                collect_variables(&statement.object, variables);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces the blank nodes of a rule conclusion by variables named after `suffix`, recording each pair
+fn existentials_as_variables(term: &Term, suffix: usize, existentials: &mut Vec<(String, String)>) -> Term {
+    match term {
+        Term::BlankNode(node) => {
+            let var = format!("_:{}.{}.sk", node.as_str(), suffix);
+            if !existentials.iter().any(|(_, existing)| *existing == var) {
+                existentials.push((node.as_str().to_string(), var.clone()));
+            }
+            Term::Variable(var)
+        }
+        Term::List(items) => Term::List(
+            items
+                .iter()
+                .map(|item| existentials_as_variables(item, suffix, existentials))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Replaces the variables of a goal by canonical names so that variants of a goal share a table
+fn variant_key(goal: &Statement) -> Statement {
+    let renaming: Substitution = variables_of(goal)
+        .into_iter()
+        .enumerate()
+        .map(|(i, var)| (var, Term::Variable(format!("v{}", i))))
+        .collect();
+    renaming.apply_statement(goal)
+}
+
+/// Bounds on the search performed by [`ProofEngine::prove`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofLimits {
+    /// Maximum number of rules chained below a goal
+    pub max_depth: usize,
+
+    /// Maximum number of facts and rule conclusions tried against goals
+    pub max_steps: usize,
+}
+
+impl Default for ProofLimits {
+    fn default() -> Self {
+        ProofLimits {
+            max_depth: 32,
+            max_steps: 100_000,
+        }
+    }
+}
+
+/// Result of trying to prove the goal of a [`ProofEngine`]
+#[derive(Debug, Clone)]
+pub enum ProofOutcome {
+    /// The goal holds, and the proof ends with a step concluding it
+    Proven(Box<Proof>),
+
+    /// The search finished without finding a proof, so the goal does not follow
+    NotProvable,
+
+    /// No proof was found before the search reached the depth or step limit of the engine's [`ProofLimits`]
+    ///
+    /// A proof may exist beyond the limits.
+    LimitReached,
+}
+
+/// How one goal statement was established by backward chaining
+#[derive(Clone)]
+enum Justification {
+    /// A fact of the knowledge base, established by the given proof step
+    Fact(usize),
+
+    /// A statement that a builtin found to hold, as instantiated by its solution
+    Builtin(Statement),
+
+    /// An instance of a rule whose conclusion contains the goal
+    Rule {
+        rule_index: usize,
+        conclusion: Arc<Formula>,
+        premises: Vec<Justification>,
+    },
+}
+
+/// The builtin statements among a step's justifications, under the final binding
+fn builtin_evidence(justifications: &[Justification], binding: &Substitution) -> Vec<Statement> {
+    let mut evidence = Vec::new();
+    for justification in justifications {
+        if let Justification::Builtin(statement) = justification {
+            let statement = binding.apply_statement(statement);
+            if !evidence.contains(&statement) {
+                evidence.push(statement);
+            }
+        }
+    }
+    evidence
+}
+
+/// A rule with its variables renamed apart for one use by the resolver
+struct RenamedRule {
+    premises: Vec<Statement>,
+
+    /// The conclusion, with blank nodes turned into the variables in `existentials`
+    conclusion: Formula,
+
+    /// Blank node label and variable for each existential of the conclusion
+    existentials: Vec<(String, String)>,
+}
+
+impl RenamedRule {
+    /// Binds each existential of the conclusion to a blank node determined by the rule instance
+    ///
+    /// The same premises always give the same blank nodes, so proving a goal
+    /// again does not produce new answers. Returns `None` if an existential
+    /// got bound to a specific term or to another existential, since the
+    /// rule does not say anything about them.
+    fn skolemize(&self, rule_index: usize, binding: &Substitution) -> Option<Substitution> {
+        let premises: Vec<Statement> = self.premises.iter().map(|premise| binding.apply_statement(premise)).collect();
+        let mut result = binding.clone();
+        let mut targets = HashSet::new();
+        for (label, existential) in &self.existentials {
+            let Term::Variable(var) = binding.apply_term(&Term::Variable(existential.clone())) else {
+                return None;
+            };
+            if !targets.insert(var.clone()) {
+                return None;
+            }
+
+            let mut hasher = DefaultHasher::new();
+            (rule_index, label, &premises).hash(&mut hasher);
+            let blank = BlankNode::new(format!("sk{:016x}", hasher.finish())).expect("hex digits form a valid blank node id");
+            result = result.unify_terms(&Term::Variable(var), &Term::BlankNode(blank))?;
+        }
+        Some(result)
+    }
+}
+
+/// Answers found so far for one tabled goal
+#[derive(Default)]
+struct Table {
+    /// Instances of the goal with the justification each was first found with
+    answers: Vec<(Statement, Justification)>,
+    seen: HashSet<Statement>,
+    state: TableState,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum TableState {
+    /// Being evaluated by the call at this position of the call stack
+    Evaluating(usize),
+
+    /// Evaluated, but possibly missing answers because the evaluation
+    /// consumed a goal that was still being evaluated or hit the depth limit
+    #[default]
+    Incomplete,
+
+    /// Holds every answer to the goal
+    Complete,
+}
+
+/// A goal being evaluated, as tracked on the resolver's call stack
+struct Frame {
+    key: Statement,
+
+    /// Lowest stack position of an evaluating goal whose answers were consumed
+    low: usize,
+
+    /// Goals evaluated below this one that can only complete together with it
+    dependents: Vec<Statement>,
+
+    /// Whether the depth limit cut off part of the evaluation
+    truncated: bool,
+}
+
+/// Tabled resolution of goals against the engine's facts and rules
+///
+/// Every goal is answered from a table shared by all its variants. The
+/// first call of a goal evaluates it against the facts and the rule
+/// conclusions, and a recursive call of a goal that is still being
+/// evaluated only consumes the answers found so far instead of evaluating it
+/// again. The outermost goal of such a cycle is re-evaluated until no table
+/// gains new answers, after which every table of the cycle is complete, so
+/// left-recursive rules terminate and still find every answer.
+struct Resolver<'a> {
+    rules: &'a [Rule],
+    facts: InternedFacts<'a>,
+    fact_steps: &'a HashMap<IdTriple, usize>,
+    builtins: &'a BuiltinRegistry,
+    limits: ProofLimits,
+
+    /// The time `time:localTime` gives during the search
+    local_time: Option<DateTime<FixedOffset>>,
+
+    /// The error that stopped the search, if a builtin raised one
+    error: RefCell<Option<BuiltinError>>,
+
+    /// Facts and rule conclusions tried so far
+    steps: Cell<usize>,
+
+    /// Number of rules and answers renamed apart so far
+    renamings: Cell<usize>,
+
+    tables: RefCell<HashMap<Statement, Table>>,
+    stack: RefCell<Vec<Frame>>,
+
+    /// Number of answers added to any table so far
+    answers_added: Cell<usize>,
+
+    /// Whether the depth or step limit cut off part of the search
+    limited: Cell<bool>,
+}
+
+impl Resolver<'_> {
+    /// Proves `goals` left to right, calling `on_solution` with each binding and one justification per goal
+    ///
+    /// A builtin goal whose arguments are not bound enough yet is put off
+    /// until a later goal binds them. Returns false as soon as `on_solution`
+    /// does, the step limit is reached or a builtin raises an error.
+    fn solve(
+        &self,
+        goals: &[Statement],
+        depth: usize,
+        binding: &Substitution,
+        on_solution: &mut dyn FnMut(&Substitution, Vec<Justification>) -> bool,
+    ) -> bool {
+        if goals.is_empty() {
+            return on_solution(binding, Vec::new());
+        }
+
+        for (index, goal) in goals.iter().enumerate() {
+            let rest = without(goals, index);
+            let Some(builtin) = self.builtins.for_predicate(&goal.predicate) else {
+                return self.solve_one(goal, depth, binding, &mut |binding, justification| {
+                    self.solve(&rest, depth, binding, &mut |binding, mut justifications| {
+                        justifications.insert(0, justification.clone());
+                        on_solution(binding, justifications)
+                    })
+                });
+            };
+
+            let context = BuiltinContext::new(self.builtins)
+                .with_knowledge_base(&self.facts)
+                .with_local_time(self.local_time);
+            let extensions = match evaluate_builtin(&context, builtin.as_ref(), goal, binding) {
+                Ok(Some(extensions)) => extensions,
+                Ok(None) => continue,
+                Err(error) => {
+                    self.error.borrow_mut().get_or_insert(error);
+                    return false;
+                }
+            };
+            for extended in extensions {
+                let evaluated = extended.apply_statement(goal);
+                let finished = self.solve(&rest, depth, &extended, &mut |binding, mut justifications| {
+                    justifications.insert(0, Justification::Builtin(evaluated.clone()));
+                    on_solution(binding, justifications)
+                });
+                if !finished {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        // Only builtins are left, and none of them can be evaluated
+        true
+    }
+
+    /// Proves a single goal from the answers in its table
+    fn solve_one(
+        &self,
+        goal: &Statement,
+        depth: usize,
+        binding: &Substitution,
+        on_solution: &mut dyn FnMut(&Substitution, Justification) -> bool,
+    ) -> bool {
+        let goal = binding.apply_statement(goal);
+        let Some(answers) = self.answers(&goal, depth) else {
             return false;
+        };
+
+        for (answer, justification) in answers {
+            let answer = if answer.is_ground() { answer } else { self.rename_answer_apart(&answer) };
+            if let Some(extended) = binding.unify_statements(&goal, &answer) {
+                if !on_solution(&extended, justification) {
+                    return false;
+                }
+            }
         }
-        
-        // This is a placeholder for a more sophisticated rule matching algorithm
-        // A real implementation would check if the premises of the rule can be unified with the given formulas
-        
-        // For now, we just check if the number of statements matches
-        for (i, premise) in self.premises.iter().enumerate() {
-            if i >= formulas.len() || premise.statements.len() != formulas[i].statements.len() {
+        true
+    }
+
+    /// Returns the answers to a goal, evaluating it first unless it is complete or already being evaluated
+    ///
+    /// Returns `None` once the step limit is reached.
+    fn answers(&self, goal: &Statement, depth: usize) -> Option<Vec<(Statement, Justification)>> {
+        let key = variant_key(goal);
+        let state = self.tables.borrow().get(&key).map(|table| table.state);
+        match state {
+            Some(TableState::Complete) => return Some(self.table_answers(&key)),
+            Some(TableState::Evaluating(position)) => {
+                let mut stack = self.stack.borrow_mut();
+                let top = stack.last_mut().expect("an evaluating goal is on the stack");
+                top.low = top.low.min(position);
+                drop(stack);
+                return Some(self.table_answers(&key));
+            }
+            _ => {}
+        }
+
+        if depth >= self.limits.max_depth {
+            self.limited.set(true);
+            if let Some(top) = self.stack.borrow_mut().last_mut() {
+                top.truncated = true;
+            }
+            let mut answers = Vec::new();
+            let finished = self.resolve_facts(goal, &mut |binding, justification| {
+                answers.push((binding.apply_statement(goal), justification));
+                true
+            });
+            return finished.then_some(answers);
+        }
+
+        self.evaluate(&key, goal, depth)?;
+        Some(self.table_answers(&key))
+    }
+
+    /// Fills the table of a goal until an evaluation round finds no new answer anywhere
+    fn evaluate(&self, key: &Statement, goal: &Statement, depth: usize) -> Option<()> {
+        let position = {
+            let mut stack = self.stack.borrow_mut();
+            let position = stack.len();
+            stack.push(Frame { key: key.clone(), low: position, dependents: Vec::new(), truncated: false });
+            position
+        };
+        self.tables.borrow_mut().entry(key.clone()).or_default().state = TableState::Evaluating(position);
+
+        loop {
+            let before = self.answers_added.get();
+            let mut record = |binding: &Substitution, justification| {
+                self.add_answer(key, binding.apply_statement(goal), justification);
+                true
+            };
+            if !self.resolve_facts(goal, &mut record) || !self.resolve_rules(goal, depth, &mut record) {
+                return None;
+            }
+            if self.answers_added.get() == before {
+                break;
+            }
+        }
+
+        let frame = self.stack.borrow_mut().pop().expect("the frame pushed above");
+        let mut tables = self.tables.borrow_mut();
+        let mut stack = self.stack.borrow_mut();
+        if frame.low >= position {
+            let state = if frame.truncated { TableState::Incomplete } else { TableState::Complete };
+            for completed in frame.dependents.iter().chain([&frame.key]) {
+                tables.get_mut(completed).expect("evaluated goals have tables").state = state;
+            }
+            if let Some(parent) = stack.last_mut() {
+                parent.truncated |= frame.truncated;
+            }
+        } else {
+            tables.get_mut(&frame.key).expect("evaluated goals have tables").state = TableState::Incomplete;
+            let parent = stack.last_mut().expect("a goal depending on an evaluating goal has a parent");
+            parent.low = parent.low.min(frame.low);
+            parent.truncated |= frame.truncated;
+            parent.dependents.extend(frame.dependents);
+            parent.dependents.push(frame.key);
+        }
+        Some(())
+    }
+
+    fn table_answers(&self, key: &Statement) -> Vec<(Statement, Justification)> {
+        self.tables.borrow()[key].answers.clone()
+    }
+
+    fn add_answer(&self, key: &Statement, answer: Statement, justification: Justification) {
+        let mut tables = self.tables.borrow_mut();
+        let table = tables.get_mut(key).expect("evaluated goals have tables");
+        if table.seen.insert(answer.clone()) {
+            table.answers.push((answer, justification));
+            self.answers_added.set(self.answers_added.get() + 1);
+        }
+    }
+
+    /// Matches a goal against the facts of the knowledge base
+    fn resolve_facts(&self, goal: &Statement, on_answer: &mut dyn FnMut(&Substitution, Justification) -> bool) -> bool {
+        let key = |term: &Term| if term.is_ground() { Some(term.clone()) } else { None };
+        let (subject, predicate, object) = (key(&goal.subject), key(&goal.predicate), key(&goal.object));
+        let candidates: Vec<Statement> = self
+            .facts
+            .matching(subject.as_ref(), predicate.as_ref(), object.as_ref(), 0..self.facts.facts.len())
+            .map(Cow::into_owned)
+            .collect();
+
+        for fact in candidates {
+            if !self.take_step() {
                 return false;
             }
+            if let Some(binding) = Substitution::new().unify_statements(goal, &fact) {
+                let triple = self.facts.interner.get_statement(&fact).expect("facts are interned");
+                if !on_answer(&binding, Justification::Fact(self.fact_steps[&triple])) {
+                    return false;
+                }
+            }
         }
-        
         true
     }
-    
-    /// Apply the rule to the given formulas
-    /// Returns the resulting formula if successful
-    pub fn apply(&self, formulas: &[Formula]) -> Result<Formula> {
-        if !self.can_apply(formulas) {
-            return Err(Error::ReasoningError(format!(
-                "Rule '{}' cannot be applied to the given formulas", self.name
-            )));
+
+    /// Unifies a goal with the conclusion of every rule and proves the premises of each match
+    fn resolve_rules(
+        &self,
+        goal: &Statement,
+        depth: usize,
+        on_answer: &mut dyn FnMut(&Substitution, Justification) -> bool,
+    ) -> bool {
+        for (rule_index, rule) in self.rules.iter().enumerate() {
+            let renamed = self.rename_apart(rule);
+            for head in &renamed.conclusion.statements {
+                if !self.take_step() {
+                    return false;
+                }
+                let Some(unifier) = Substitution::new().unify_statements(goal, head) else {
+                    continue;
+                };
+                let finished = self.solve(&renamed.premises, depth + 1, &unifier, &mut |binding, premises| {
+                    let Some(binding) = renamed.skolemize(rule_index, binding) else {
+                        return true;
+                    };
+                    let conclusion = Arc::new(binding.apply_formula(&renamed.conclusion));
+                    on_answer(&binding, Justification::Rule { rule_index, conclusion, premises })
+                });
+                if !finished {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Counts one resolution step, returning false once the limit is exceeded
+    fn take_step(&self) -> bool {
+        self.steps.set(self.steps.get() + 1);
+        let within = self.steps.get() <= self.limits.max_steps;
+        if !within {
+            self.limited.set(true);
+        }
+        within
+    }
+
+    fn next_suffix(&self) -> usize {
+        let suffix = self.renamings.get();
+        self.renamings.set(suffix + 1);
+        suffix
+    }
+
+    /// Gives the variables of a rule fresh names and turns its conclusion's blank nodes into existential variables
+    fn rename_apart(&self, rule: &Rule) -> RenamedRule {
+        let suffix = self.next_suffix();
+        let premises = rule.premise_patterns();
+        let mut variables = Vec::new();
+        for statement in premises.iter().chain(&rule.conclusion.statements) {
+            for var in variables_of(statement) {
+                if !variables.contains(&var) {
+                    variables.push(var);
+                }
+            }
+        }
+        let renaming: Substitution = variables
+            .into_iter()
+            .map(|var| {
+                let renamed = Term::Variable(format!("{}.{}", var, suffix));
+                (var, renamed)
+            })
+            .collect();
+
+        let mut existentials = Vec::new();
+        let mut conclusion = renaming.apply_formula(&rule.conclusion);
+        conclusion.statements = conclusion
+            .statements
+            .iter()
+            .map(|statement| Statement {
+                subject: existentials_as_variables(&statement.subject, suffix, &mut existentials),
+                predicate: existentials_as_variables(&statement.predicate, suffix, &mut existentials),
+                object: existentials_as_variables(&statement.object, suffix, &mut existentials),
+            })
+            .collect();
+
+        RenamedRule {
+            premises: premises.iter().map(|premise| renaming.apply_statement(premise)).collect(),
+            conclusion,
+            existentials,
         }
-        
-        // This is a placeholder for a real rule application algorithm
-        // A real implementation would perform proper unification and substitution
-        
-        // For now, we just return a clone of the conclusion
-        Ok(self.conclusion.clone())
     }
+
+    /// Gives the variables left in a tabled answer fresh names
+    fn rename_answer_apart(&self, answer: &Statement) -> Statement {
+        let suffix = self.next_suffix();
+        let renaming: Substitution = variables_of(answer)
+            .into_iter()
+            .map(|var| {
+                let renamed = Term::Variable(format!("{}.{}", var, suffix));
+                (var, renamed)
+            })
+            .collect();
+        renaming.apply_statement(answer)
+    }
+}
+
+/// Counters describing the work done by [`ProofEngine::saturate`]
+///
+/// The counters accumulate over the lifetime of the engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReasoningStats {
+    /// Passes over the rules
+    pub rounds: usize,
+
+    /// Rule instantiations found by matching premises against the facts
+    pub rule_firings: usize,
+
+    /// Statements added to the knowledge base by saturation
+    pub derived_statements: usize,
 }
 
 /// The main proof engine that orchestrates the reasoning process
@@ -85,32 +1025,95 @@ pub struct ProofEngine {
     
     /// Current knowledge base (derived formulas)
     knowledge_base: Vec<Formula>,
+
+    /// Dictionary of every term the engine has seen
+    interner: Interner,
+
+    /// Every statement of the knowledge base, interned and indexed
+    facts: TripleStore<IdTriple>,
+
+    /// The proof step that first established each fact
+    fact_steps: HashMap<IdTriple, usize>,
+
+    /// Rule instantiations already derived during saturation, so none fires twice
+    fired: HashSet<(usize, Formula)>,
+
+    /// For each rule, the number of facts it has been matched against, if any
+    evaluated: Vec<Option<usize>>,
+
+    /// Work done by saturation so far
+    stats: ReasoningStats,
+
+    /// Bounds on backward chaining
+    limits: ProofLimits,
+
+    /// What saturation does with rules that cannot be stratified
+    stratification: StratificationPolicy,
+
+    /// Builtin predicates, by IRI
+    builtins: BuiltinRegistry,
+
+    /// The clock `time:localTime` reads
+    clock: Box<dyn Clock>,
+
+    /// The time read from `clock` when the current or last run started
+    local_time: Option<DateTime<FixedOffset>>,
     
     /// The proof being constructed
     proof: Proof,
 }
 
 impl ProofEngine {
-    /// Create a new proof engine with default rules
+    /// Create a new proof engine with the standard builtins
     pub fn new() -> Self {
-        let mut engine = ProofEngine {
+        ProofEngine {
             rules: Vec::new(),
             axioms: Vec::new(),
             knowledge_base: Vec::new(),
+            interner: Interner::new(),
+            facts: TripleStore::new(),
+            fact_steps: HashMap::new(),
+            fired: HashSet::new(),
+            evaluated: Vec::new(),
+            stats: ReasoningStats::default(),
+            limits: ProofLimits::default(),
+            stratification: StratificationPolicy::default(),
+            builtins: BuiltinRegistry::standard(),
+            clock: Box::new(SystemClock),
+            local_time: None,
             proof: Proof::new(),
-        };
-        
-        engine.add_builtin_rules();
-        engine
+        }
     }
-    
-    /// Add built-in rules to the engine
-    fn add_builtin_rules(&mut self) {
-        // Placeholder for adding built-in rules
-        // In a complete implementation, this would add rules for N3 logic
-        
-        // For now, we just have a placeholder
-        // TODO: Add actual N3 rules
+
+    /// Register a builtin for a predicate IRI, replacing any previous one
+    ///
+    /// Premises and goals with this predicate are evaluated by the builtin
+    /// from then on instead of being matched against the knowledge base.
+    pub fn register_builtin(&mut self, iri: &str, builtin: impl Builtin + 'static) {
+        self.builtins.register(iri, builtin);
+    }
+
+    /// Replace every builtin known to the engine
+    pub fn set_builtins(&mut self, builtins: BuiltinRegistry) {
+        self.builtins = builtins;
+    }
+
+    /// Set the clock `time:localTime` reads
+    ///
+    /// The clock is read once at the start of every call of
+    /// [`ProofEngine::saturate`], [`ProofEngine::prove`] or
+    /// [`ProofEngine::apply_rule`], and that reading
+    /// is the time for the whole call, including nested `log:conclusion`
+    /// engines; the next call reads the clock again. Use a
+    /// [`FixedClock`](crate::FixedClock) for reproducible results. The time
+    /// read is kept in the evidence of every proof step that used it.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Returns the builtins known to the engine
+    pub fn builtins(&self) -> &BuiltinRegistry {
+        &self.builtins
     }
     
     /// Check if the engine is ready to perform reasoning
@@ -119,38 +1122,178 @@ impl ProofEngine {
     }
     
     /// Add an axiom to the engine
+    ///
+    /// Returns the index of the axiom in the knowledge base, which is also
+    /// the index of its proof step and can be passed to [`ProofEngine::apply_rule`].
     pub fn add_axiom(&mut self, axiom: Formula) -> usize {
-        let axiom_index = self.axioms.len();
         self.axioms.push(axiom.clone());
-        self.knowledge_base.push(axiom.clone());
         
         // Add as a proof step with no premises
         let step = ProofStep::new(axiom, "axiom", Vec::new())
             .with_description("Axiom added to the proof");
-        self.proof.add_step(step);
-        
-        axiom_index
+        self.record_step(step)
     }
     
     /// Add a rule to the engine
     pub fn add_rule(&mut self, rule: Rule) -> usize {
         let rule_index = self.rules.len();
         self.rules.push(rule);
+        self.evaluated.push(None);
         rule_index
     }
     
+    /// Add the facts and rules of a parsed document to the engine
+    ///
+    /// The facts become a single axiom, if there are any, and every forward
+    /// and backward rule is added in order. Queries are not added.
+    pub fn add_program(&mut self, program: &Program) {
+        if !program.facts.statements.is_empty() {
+            self.add_axiom(program.facts.clone());
+        }
+        for rule in program.rules() {
+            self.add_rule(rule.clone());
+        }
+    }
+    
     /// Set the goal for the proof
     pub fn set_goal(&mut self, goal: Formula) {
         self.proof = self.proof.clone().with_goal(goal);
     }
     
+    /// Set the bounds on the search performed by [`ProofEngine::prove`]
+    pub fn set_limits(&mut self, limits: ProofLimits) {
+        self.limits = limits;
+    }
+
+    /// Set what [`ProofEngine::saturate`] does with rules that search the knowledge base for their own conclusions
+    pub fn set_stratification_policy(&mut self, policy: StratificationPolicy) {
+        self.stratification = policy;
+    }
+    
     /// Check if the current proof establishes the goal
     pub fn goal_proven(&self) -> Result<bool> {
         self.proof.is_valid()
     }
+
+    /// Prove the goal by backward chaining from it to the knowledge base
+    ///
+    /// Each goal statement is resolved against the facts of the knowledge
+    /// base and against the conclusions of every rule, forward and backward,
+    /// whose premises then become new goals. Variables and blank nodes in the
+    /// goal may be bound to any term; the first solution found is used.
+    ///
+    /// Goals are tabled: the answers to a goal are computed once and shared
+    /// by every call of it or of a variant of it, which makes left-recursive
+    /// rules such as transitivity terminate. Blank nodes in a rule conclusion
+    /// stand for the same blank node each time the same rule instance is used.
+    ///
+    /// On success, every rule instance used is added to the knowledge base
+    /// as a proof step, followed by a final step concluding the instantiated
+    /// goal from them, and the resulting proof is returned. Otherwise the
+    /// outcome tells a search that found nothing apart from one cut off by
+    /// the engine's limits.
+    pub fn prove(&mut self) -> Result<ProofOutcome> {
+        let goal = self
+            .proof
+            .goal
+            .clone()
+            .ok_or_else(|| Error::ReasoningError("No goal has been set".to_string()))?;
+        let goals: Vec<Statement> = goal.statements.iter().map(as_pattern).collect();
+        let mut pattern = goal.clone();
+        pattern.statements = goals.iter().cloned().collect();
+
+        self.local_time = Some(self.clock.now());
+        let resolver = Resolver {
+            rules: &self.rules,
+            facts: InternedFacts { interner: &self.interner, facts: &self.facts },
+            fact_steps: &self.fact_steps,
+            builtins: &self.builtins,
+            limits: self.limits,
+            local_time: self.local_time,
+            error: RefCell::new(None),
+            steps: Cell::new(0),
+            renamings: Cell::new(0),
+            tables: RefCell::new(HashMap::new()),
+            stack: RefCell::new(Vec::new()),
+            answers_added: Cell::new(0),
+            limited: Cell::new(false),
+        };
+        let mut solution = None;
+        resolver.solve(&goals, 0, &Substitution::new(), &mut |binding, justifications| {
+            solution = Some((binding.clone(), justifications));
+            false
+        });
+        if let Some(error) = resolver.error.take() {
+            return Err(error.into());
+        }
+        let Some((binding, justifications)) = solution else {
+            if resolver.limited.get() {
+                return Ok(ProofOutcome::LimitReached);
+            }
+            return Ok(ProofOutcome::NotProvable);
+        };
+
+        let mut recorded = HashMap::new();
+        let mut premises: Vec<usize> = justifications
+            .iter()
+            .filter_map(|justification| self.record_justification(justification, &binding, &mut recorded))
+            .collect();
+        premises.sort_unstable();
+        premises.dedup();
+
+        let conclusion = binding.apply_formula(&pattern);
+        let step = ProofStep::new(conclusion, "goal", premises)
+            .with_description("Goal established by backward chaining")
+            .with_evidence(builtin_evidence(&justifications, &binding));
+        self.record_step(step);
+        Ok(ProofOutcome::Proven(Box::new(self.get_proof())))
+    }
+
+    /// Adds the proof steps for a justification found by backward chaining, returning the step concluding it
+    ///
+    /// Builtin statements are not concluded by any step; they are recorded
+    /// as the evidence of the step that relied on them instead.
+    fn record_justification(
+        &mut self,
+        justification: &Justification,
+        binding: &Substitution,
+        recorded: &mut HashMap<(usize, Formula), usize>,
+    ) -> Option<usize> {
+        let (rule_index, conclusion, premises) = match justification {
+            Justification::Fact(step) => return Some(*step),
+            Justification::Builtin(_) => return None,
+            Justification::Rule { rule_index, conclusion, premises } => (rule_index, conclusion, premises),
+        };
+
+        let mut premise_steps: Vec<usize> = premises
+            .iter()
+            .filter_map(|premise| self.record_justification(premise, binding, recorded))
+            .collect();
+        premise_steps.sort_unstable();
+        premise_steps.dedup();
+
+        let conclusion = binding.apply_formula(conclusion);
+        if let Some(&step) = recorded.get(&(*rule_index, conclusion.clone())) {
+            return Some(step);
+        }
+        let name = self.rules[*rule_index].name.clone();
+        let step = ProofStep::new(conclusion.clone(), &name, premise_steps)
+            .with_description(&format!("Applied rule '{}' to prove a goal", name))
+            .with_evidence(builtin_evidence(premises, binding));
+        let index = self.record_step(step);
+        recorded.insert((*rule_index, conclusion), index);
+        Some(index)
+    }
     
     /// Apply a rule to the current knowledge base
-    pub fn apply_rule(&mut self, rule_index: usize, premise_indices: &[usize]) -> Result<usize> {
+    ///
+    /// The rule's premises are matched against the knowledge base entries at
+    /// `premise_indices`, evaluating builtin premises with the engine's
+    /// builtins, which may search the whole knowledge base. Every distinct
+    /// instantiation of the conclusion is added to the knowledge base as its
+    /// own proof step, with the builtin statements it relied on as evidence,
+    /// and their indices are returned.
+    pub fn apply_rule(&mut self, rule_index: usize, premise_indices: &[usize]) -> Result<Vec<usize>> {
         // Check if rule index is valid
         if rule_index >= self.rules.len() {
             return Err(Error::ReasoningError(format!("Invalid rule index: {}", rule_index)));
@@ -168,22 +1311,242 @@ impl ProofEngine {
         }
         
         // Apply the rule
-        let conclusion = rule.apply(&premises)?;
-        
-        // Add to knowledge base
-        let idx = self.knowledge_base.len();
-        self.knowledge_base.push(conclusion.clone());
-        
-        // Add to proof
-        let step = ProofStep::new(
-            conclusion, 
-            &rule.name, 
-            premise_indices.to_vec()
-        ).with_description(&format!("Applied rule '{}'", rule.name));
-        
+        let source = InternedFacts { interner: &self.interner, facts: &self.facts };
+        let context = BuiltinContext::new(&self.builtins)
+            .with_knowledge_base(&source)
+            .with_local_time(Some(self.clock.now()));
+        let conclusions = rule.conclusions(rule.matches_in(&premises, &context)?)?;
+        let patterns = rule.premise_patterns();
+        let steps: Vec<ProofStep> = conclusions
+            .into_iter()
+            .map(|(conclusion, binding)| {
+                let evidence = patterns
+                    .iter()
+                    .filter(|pattern| self.is_builtin(pattern))
+                    .map(|pattern| binding.apply_statement(pattern))
+                    .collect();
+                ProofStep::new(conclusion, &rule.name, premise_indices.to_vec())
+                    .with_description(&format!("Applied rule '{}'", rule.name))
+                    .with_evidence(evidence)
+            })
+            .collect();
+
+        Ok(steps.into_iter().map(|step| self.record_step(step)).collect())
+    }
+
+    /// Fire every rule against the knowledge base until nothing new can be derived
+    ///
+    /// This computes the deductive closure of the axioms under the registered
+    /// rules, like running EYE with `--pass`. Each rule application that adds
+    /// at least one new statement becomes a proof step whose premises are the
+    /// steps that established the matched statements. Returns the statements
+    /// derived by this call.
+    ///
+    /// Evaluation is semi-naive: every rule remembers how many facts it has
+    /// already been matched against and is only joined against the facts
+    /// added since, so each binding of a rule's premises is found once over
+    /// the lifetime of the engine, including across calls. Backward rules
+    /// are not fired.
+    ///
+    /// Rules are saturated in strata, so that a rule searching the knowledge
+    /// base with a builtin such as `log:includes` or `log:notIncludes` only
+    /// fires once every rule concluding what it searches for has been
+    /// saturated, whatever order the rules are written in. Rule
+    /// sets where that is impossible are rejected with
+    /// [`Error::StratificationError`] unless the engine's
+    /// [`StratificationPolicy`] says to warn instead. Rules searching the
+    /// knowledge base are matched against every fact again whenever facts
+    /// were added, so a later call also derives what such a search finds
+    /// then, e.g. a `log:collectAllIn` list with the new facts. Nothing is
+    /// retracted though: conclusions of a negation that held, or of a list
+    /// collected before, stay in the knowledge base.
+    pub fn saturate(&mut self) -> Result<TripleStore> {
+        self.local_time = Some(self.clock.now());
+        let strata = match stratify(&self.rules, &self.builtins) {
+            Ok(strata) => strata,
+            Err(rule) => {
+                let message = format!("rule '{}' searches the knowledge base for its own conclusions", rule);
+                if self.stratification == StratificationPolicy::Reject {
+                    return Err(Error::StratificationError(message));
+                }
+                log::warn!("{}; saturating all rules in one stratum", message);
+                vec![0; self.rules.len()]
+            }
+        };
+
+        let mut derived = TripleStore::new();
+        for stratum in 0..=strata.iter().copied().max().unwrap_or(0) {
+            let rules: Vec<usize> = (0..self.rules.len()).filter(|&rule_index| strata[rule_index] <= stratum).collect();
+            while rules.iter().any(|&rule_index| self.is_pending(rule_index)) {
+                self.stats.rounds += 1;
+                for &rule_index in &rules {
+                    if !self.is_pending(rule_index) {
+                        continue;
+                    }
+                    let end = self.facts.len();
+                    let start = self.evaluated[rule_index];
+
+                    let patterns = self.rules[rule_index].premise_patterns();
+                    let bindings = self.new_bindings(&patterns, start, end)?;
+                    self.evaluated[rule_index] = Some(end);
+
+                    for binding in bindings {
+                        self.stats.rule_firings += 1;
+                        self.fire(rule_index, &patterns, &binding, &mut derived);
+                    }
+                }
+            }
+        }
+        Ok(derived)
+    }
+
+    /// Whether a forward rule has not yet been matched against every fact
+    fn is_pending(&self, rule_index: usize) -> bool {
+        self.rules[rule_index].direction == RuleDirection::Forward
+            && self.evaluated[rule_index] != Some(self.facts.len())
+    }
+
+    /// Finds the bindings of `patterns` over the first `end` facts that use at least one fact at or after `start`
+    ///
+    /// A rule that has never been evaluated (`start` is `None`) is matched
+    /// against every fact, which also lets a rule without premises fire once.
+    /// Otherwise the join is split by the first pattern matched against a new
+    /// fact: earlier patterns only see old facts and later ones see all, so
+    /// no binding is found twice. Builtin premises are not matched against
+    /// facts and so never play the part of the new fact. A rule whose
+    /// premises are all builtins, or which searches the knowledge base with
+    /// a builtin such as `log:includes` or `log:collectAllIn`, is matched in
+    /// full again instead, since what the builtins find may change with any
+    /// added fact.
+    fn new_bindings(&self, patterns: &[Statement], start: Option<usize>, end: usize) -> Result<Vec<Substitution>> {
+        let source = InternedFacts { interner: &self.interner, facts: &self.facts };
+        let context = BuiltinContext::new(&self.builtins)
+            .with_knowledge_base(&source)
+            .with_local_time(self.local_time);
+        let mut bindings = Vec::new();
+        let mut collect = |binding| {
+            bindings.push(binding);
+            true
+        };
+        let pattern = |statement: &Statement, window: Range<usize>| Pattern {
+            statement: statement.clone(),
+            window,
+            builtin: self.builtins.for_predicate(&statement.predicate).cloned(),
+        };
+
+        let only_builtins = !patterns.is_empty() && patterns.iter().all(|statement| self.is_builtin(statement));
+        let searches = patterns.iter().any(|statement| self.is_builtin(statement) && scoped_query(statement).is_some());
+        let Some(start) = start.filter(|_| !only_builtins && !searches) else {
+            let all: Vec<Pattern> = patterns.iter().map(|statement| pattern(statement, 0..end)).collect();
+            join(&all, &source, &context, Substitution::new(), &mut collect)?;
+            return Ok(bindings);
+        };
+        for delta in (0..patterns.len()).filter(|&i| !self.is_builtin(&patterns[i])) {
+            let windowed: Vec<Pattern> = patterns
+                .iter()
+                .enumerate()
+                .map(|(i, statement)| {
+                    let window = match i.cmp(&delta) {
+                        std::cmp::Ordering::Less => 0..start,
+                        std::cmp::Ordering::Equal => start..end,
+                        std::cmp::Ordering::Greater => 0..end,
+                    };
+                    pattern(statement, window)
+                })
+                .collect();
+            join(&windowed, &source, &context, Substitution::new(), &mut collect)?;
+        }
+        Ok(bindings)
+    }
+
+    /// Whether a statement's predicate is a registered builtin
+    fn is_builtin(&self, statement: &Statement) -> bool {
+        self.builtins.for_predicate(&statement.predicate).is_some()
+    }
+
+    /// Records the conclusion of one rule instantiation
+    fn fire(&mut self, rule_index: usize, patterns: &[Statement], binding: &Substitution, derived: &mut TripleStore) {
+        let rule = &self.rules[rule_index];
+        let conclusion = binding.apply_formula(&rule.conclusion);
+        if !self.fired.insert((rule_index, conclusion.clone())) {
+            return;
+        }
+
+        let conclusion = with_fresh_blank_nodes(&conclusion);
+        let new_statements: Vec<Statement> = conclusion
+            .statements
+            .iter()
+            .filter(|statement| !self.contains_fact(statement))
+            .cloned()
+            .collect();
+        if new_statements.is_empty() {
+            return;
+        }
+
+        let mut premises: Vec<usize> = patterns
+            .iter()
+            .filter(|pattern| !self.is_builtin(pattern))
+            .filter_map(|pattern| {
+                let matched = binding.apply_statement(pattern);
+                let triple = self.interner.get_statement(&matched)?;
+                self.fact_steps.get(&triple).copied()
+            })
+            .collect();
+        premises.sort_unstable();
+        premises.dedup();
+        let evidence = patterns
+            .iter()
+            .filter(|pattern| self.is_builtin(pattern))
+            .map(|pattern| binding.apply_statement(pattern))
+            .collect();
+
+        let step = ProofStep::new(conclusion, &rule.name, premises)
+            .with_description(&format!("Applied rule '{}'", rule.name))
+            .with_evidence(evidence);
+        self.record_step(step);
+        self.stats.derived_statements += new_statements.len();
+        derived.extend(new_statements);
+    }
+
+    /// Adds a proof step and its conclusion to the knowledge base, returning its index
+    fn record_step(&mut self, step: ProofStep) -> usize {
+        let index = self.knowledge_base.len();
+        for statement in &step.conclusion.statements {
+            let triple = self.interner.intern_statement(statement);
+            if self.facts.insert(triple) {
+                self.fact_steps.insert(triple, index);
+            }
+        }
+        self.knowledge_base.push(step.conclusion.clone());
         self.proof.add_step(step);
-        
-        Ok(idx)
+        index
+    }
+
+    /// Returns every statement in the knowledge base as one formula
+    pub fn facts(&self) -> Formula {
+        self.interner.resolve_store(&self.facts)
+    }
+
+    /// Returns the number of distinct statements in the knowledge base
+    pub fn fact_count(&self) -> usize {
+        self.facts.len()
+    }
+
+    /// Checks whether a statement is in the knowledge base
+    pub fn contains_fact(&self, statement: &Statement) -> bool {
+        self.interner
+            .get_statement(statement)
+            .is_some_and(|triple| self.facts.contains(&triple))
+    }
+
+    /// Returns counters describing the work done by saturation so far
+    pub fn stats(&self) -> ReasoningStats {
+        self.stats
+    }
+
+    /// Returns the dictionary of terms known to the engine
+    pub fn interner(&self) -> &Interner {
+        &self.interner
     }
     
     /// Get the current proof
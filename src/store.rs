@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Index;
+
+use crate::model::{Statement, Term};
+
+/// A subject-predicate-object triple that can be kept in a [`TripleStore`]
+///
+/// Implemented by [`Statement`] and by the interned
+/// [`IdTriple`](crate::IdTriple) the proof engine uses internally.
+pub trait Triple: Clone + Eq + Hash {
+    /// The type of the subject, predicate and object
+    type Term: Clone + Eq + Hash;
+
+    /// Builds a triple from its parts
+    fn from_terms(subject: Self::Term, predicate: Self::Term, object: Self::Term) -> Self;
+
+    /// The subject of the triple
+    fn subject(&self) -> &Self::Term;
+
+    /// The predicate of the triple
+    fn predicate(&self) -> &Self::Term;
+
+    /// The object of the triple
+    fn object(&self) -> &Self::Term;
+}
+
+impl Triple for Statement {
+    type Term = Term;
+
+    fn from_terms(subject: Term, predicate: Term, object: Term) -> Self {
+        Statement { subject, predicate, object }
+    }
+
+    fn subject(&self) -> &Term {
+        &self.subject
+    }
+
+    fn predicate(&self) -> &Term {
+        &self.predicate
+    }
+
+    fn object(&self) -> &Term {
+        &self.object
+    }
+}
+
+/// Triple ids grouped by two of their terms, e.g. subject then predicate
+type TermIndex<K> = HashMap<K, HashMap<K, Vec<usize>>>;
+
+/// An indexed set of triples, by default of [`Statement`]s
+///
+/// Triples are kept once each, in insertion order, and indexed by
+/// subject-predicate-object, predicate-object-subject and
+/// object-subject-predicate so that [`TripleStore::matching`] can answer a
+/// pattern with any combination of bound terms without scanning the store.
+#[derive(Clone)]
+pub struct TripleStore<T: Triple = Statement> {
+    statements: Vec<T>,
+    ids: HashMap<T, usize>,
+    spo: TermIndex<T::Term>,
+    pos: TermIndex<T::Term>,
+    osp: TermIndex<T::Term>,
+}
+
+impl<T: Triple> Default for TripleStore<T> {
+    fn default() -> Self {
+        TripleStore {
+            statements: Vec::new(),
+            ids: HashMap::new(),
+            spo: HashMap::new(),
+            pos: HashMap::new(),
+            osp: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Triple> TripleStore<T> {
+    /// Creates a new empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of triples in the store
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Returns true if the store contains no statements
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Adds a statement, returning false if it was already present
+    pub fn insert(&mut self, statement: T) -> bool {
+        if self.ids.contains_key(&statement) {
+            return false;
+        }
+
+        let id = self.statements.len();
+        add_to_index(&mut self.spo, statement.subject(), statement.predicate(), id);
+        add_to_index(&mut self.pos, statement.predicate(), statement.object(), id);
+        add_to_index(&mut self.osp, statement.object(), statement.subject(), id);
+        self.ids.insert(statement.clone(), id);
+        self.statements.push(statement);
+        true
+    }
+
+    /// Removes a statement, returning false if it was not present
+    ///
+    /// The last statement takes the place of the removed one, so removal
+    /// does not preserve insertion order.
+    pub fn remove(&mut self, statement: &T) -> bool {
+        let Some(id) = self.ids.remove(statement) else {
+            return false;
+        };
+
+        remove_from_index(&mut self.spo, statement.subject(), statement.predicate(), id);
+        remove_from_index(&mut self.pos, statement.predicate(), statement.object(), id);
+        remove_from_index(&mut self.osp, statement.object(), statement.subject(), id);
+
+        let last = self.statements.len() - 1;
+        self.statements.swap_remove(id);
+        if id != last {
+            let moved = &self.statements[id];
+            self.ids.insert(moved.clone(), id);
+            rename_in_index(&mut self.spo, moved.subject(), moved.predicate(), last, id);
+            rename_in_index(&mut self.pos, moved.predicate(), moved.object(), last, id);
+            rename_in_index(&mut self.osp, moved.object(), moved.subject(), last, id);
+        }
+        true
+    }
+
+    /// Returns true if the store contains the statement
+    pub fn contains(&self, statement: &T) -> bool {
+        self.ids.contains_key(statement)
+    }
+
+    /// Returns the statement at `index` in insertion order
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.statements.get(index)
+    }
+
+    /// Returns the statements as a slice, in insertion order
+    pub fn as_slice(&self) -> &[T] {
+        &self.statements
+    }
+
+    /// Iterates over the statements in insertion order
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.statements.iter()
+    }
+
+    /// Removes every statement from the store
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Iterates over the statements matching a pattern
+    ///
+    /// Each of `subject`, `predicate` and `object` is either bound to a term
+    /// that must match exactly or `None` to match anything. Terms are
+    /// compared as they are, so a variable in the store only matches the same
+    /// variable. Matches are returned in no particular order.
+    pub fn matching<'a>(
+        &'a self,
+        subject: Option<&T::Term>,
+        predicate: Option<&T::Term>,
+        object: Option<&T::Term>,
+    ) -> impl Iterator<Item = &'a T> + 'a {
+        self.matching_indices(subject, predicate, object)
+            .map(move |id| &self.statements[id])
+    }
+
+    /// Like [`TripleStore::matching`], but yields the insertion-order index of each match
+    ///
+    /// Indices are stable while nothing is removed, so callers can tell
+    /// statements added before and after some point apart.
+    pub fn matching_indices<'a>(
+        &'a self,
+        subject: Option<&T::Term>,
+        predicate: Option<&T::Term>,
+        object: Option<&T::Term>,
+    ) -> Box<dyn Iterator<Item = usize> + 'a> {
+        match (subject, predicate, object) {
+            (Some(subject), Some(predicate), Some(object)) => {
+                let statement = T::from_terms(subject.clone(), predicate.clone(), object.clone());
+                Box::new(self.ids.get(&statement).copied().into_iter())
+            }
+            (Some(subject), Some(predicate), None) => lookup_pair(&self.spo, subject, predicate),
+            (None, Some(predicate), Some(object)) => lookup_pair(&self.pos, predicate, object),
+            (Some(subject), None, Some(object)) => lookup_pair(&self.osp, object, subject),
+            (Some(subject), None, None) => lookup_first(&self.spo, subject),
+            (None, Some(predicate), None) => lookup_first(&self.pos, predicate),
+            (None, None, Some(object)) => lookup_first(&self.osp, object),
+            (None, None, None) => Box::new(0..self.statements.len()),
+        }
+    }
+}
+
+fn add_to_index<K: Clone + Eq + Hash>(index: &mut TermIndex<K>, first: &K, second: &K, id: usize) {
+    index
+        .entry(first.clone())
+        .or_default()
+        .entry(second.clone())
+        .or_default()
+        .push(id);
+}
+
+fn remove_from_index<K: Eq + Hash>(index: &mut TermIndex<K>, first: &K, second: &K, id: usize) {
+    let Some(seconds) = index.get_mut(first) else {
+        return;
+    };
+    if let Some(ids) = seconds.get_mut(second) {
+        ids.retain(|&existing| existing != id);
+        if ids.is_empty() {
+            seconds.remove(second);
+        }
+    }
+    if seconds.is_empty() {
+        index.remove(first);
+    }
+}
+
+fn rename_in_index<K: Eq + Hash>(index: &mut TermIndex<K>, first: &K, second: &K, from: usize, to: usize) {
+    if let Some(ids) = index.get_mut(first).and_then(|seconds| seconds.get_mut(second)) {
+        for id in ids.iter_mut().filter(|id| **id == from) {
+            *id = to;
+        }
+    }
+}
+
+fn lookup_pair<'a, K: Eq + Hash>(index: &'a TermIndex<K>, first: &K, second: &K) -> Box<dyn Iterator<Item = usize> + 'a> {
+    match index.get(first).and_then(|seconds| seconds.get(second)) {
+        Some(ids) => Box::new(ids.iter().copied()),
+        None => Box::new(std::iter::empty()),
+    }
+}
+
+fn lookup_first<'a, K: Eq + Hash>(index: &'a TermIndex<K>, first: &K) -> Box<dyn Iterator<Item = usize> + 'a> {
+    match index.get(first) {
+        Some(seconds) => Box::new(seconds.values().flatten().copied()),
+        None => Box::new(std::iter::empty()),
+    }
+}
+
+impl<T: Triple + fmt::Debug> fmt::Debug for TripleStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.statements).finish()
+    }
+}
+
+/// Stores are equal when they contain the same statements, in any order
+impl<T: Triple> PartialEq for TripleStore<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && other.iter().all(|statement| self.contains(statement))
+    }
+}
+
+impl<T: Triple> Eq for TripleStore<T> {}
+
+/// A store equals a list of statements when both hold the same statements, in any order
+impl PartialEq<Vec<Statement>> for TripleStore {
+    fn eq(&self, other: &Vec<Statement>) -> bool {
+        self.len() == other.len() && other.iter().all(|statement| self.contains(statement))
+    }
+}
+
+impl<T: Triple> Index<usize> for TripleStore<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.statements[index]
+    }
+}
+
+impl<T: Triple> FromIterator<T> for TripleStore<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut store = TripleStore::new();
+        store.extend(iter);
+        store
+    }
+}
+
+impl<T: Triple> Extend<T> for TripleStore<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for statement in iter {
+            self.insert(statement);
+        }
+    }
+}
+
+impl<T: Triple> IntoIterator for TripleStore<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.into_iter()
+    }
+}
+
+impl<'a, T: Triple> IntoIterator for &'a TripleStore<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.iter()
+    }
+}
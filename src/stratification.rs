@@ -0,0 +1,103 @@
+use crate::builtin::BuiltinRegistry;
+use crate::builtin_log::scoped_query;
+use crate::model::{Formula, Term};
+use crate::reasoner::{Rule, RuleDirection};
+
+/// What saturation does with rules that search the knowledge base for their own conclusions
+///
+/// Builtins such as `log:includes` and `log:notIncludes` with the knowledge
+/// base as scope hold or not depending on which facts have been derived so
+/// far. Saturation evaluates rules in strata so that every such search only
+/// runs once the facts it looks at are final, which is impossible when a
+/// rule searches for what it, directly or through other rules, concludes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StratificationPolicy {
+    /// Refuse to saturate such rules
+    #[default]
+    Reject,
+
+    /// Log a warning and saturate them anyway, in one stratum
+    ///
+    /// The result may then depend on the order the rules are written in.
+    Warn,
+}
+
+/// A predicate a rule's premises depend on
+struct Dependency<'a> {
+    predicate: &'a Term,
+    /// Whether the knowledge base is searched for it, rather than matched against
+    searched: bool,
+}
+
+/// Collects the predicates a formula's statements depend on
+fn dependencies<'a>(formula: &'a Formula, searched: bool, builtins: &BuiltinRegistry, found: &mut Vec<Dependency<'a>>) {
+    for statement in formula.statements.iter() {
+        if builtins.for_predicate(&statement.predicate).is_none() {
+            found.push(Dependency { predicate: &statement.predicate, searched });
+        } else if let Some(patterns) = scoped_query(statement) {
+            for pattern in patterns {
+                dependencies(pattern, true, builtins, found);
+            }
+        }
+    }
+}
+
+/// Whether a statement with predicate `concluded` can match a premise with predicate `premise`
+fn may_match(concluded: &Term, premise: &Term) -> bool {
+    matches!(concluded, Term::Variable(_)) || matches!(premise, Term::Variable(_)) || concluded == premise
+}
+
+/// Assigns every rule the stratum it is saturated in
+///
+/// A rule's stratum is at least that of every forward rule concluding a
+/// predicate its premises match, and higher than that of every forward
+/// rule concluding a predicate it searches the knowledge base for with a
+/// builtin such as `log:includes`. A variable predicate matches any
+/// predicate. Backward rules are not saturated and are in stratum 0.
+/// Returns the name of a rule that searches for its own conclusions if
+/// there is one.
+pub(crate) fn stratify(rules: &[Rule], builtins: &BuiltinRegistry) -> Result<Vec<usize>, String> {
+    let forward = |rule: &Rule| rule.direction == RuleDirection::Forward;
+    let premises: Vec<Vec<Dependency>> = rules
+        .iter()
+        .map(|rule| {
+            let mut found = Vec::new();
+            if forward(rule) {
+                for premise in &rule.premises {
+                    dependencies(premise, false, builtins, &mut found);
+                }
+            }
+            found
+        })
+        .collect();
+
+    let mut strata = vec![0; rules.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, dependencies) in premises.iter().enumerate() {
+            for dependency in dependencies {
+                for (producer, rule) in rules.iter().enumerate() {
+                    let concludes = forward(rule)
+                        && rule
+                            .conclusion
+                            .statements
+                            .iter()
+                            .any(|statement| may_match(&statement.predicate, dependency.predicate));
+                    if !concludes {
+                        continue;
+                    }
+                    let required = strata[producer] + usize::from(dependency.searched);
+                    if required > strata[index] {
+                        if required > rules.len() {
+                            return Err(rules[index].name.clone());
+                        }
+                        strata[index] = required;
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+    Ok(strata)
+}
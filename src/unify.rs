@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::model::{Formula, Statement, Term};
+
+/// A set of variable bindings
+///
+/// Substitutions built by unification are idempotent: no bound variable
+/// occurs in any of the bound terms, so applying a substitution once is
+/// enough. Substitutions built by one-way matching bind pattern variables to
+/// terms of the matched data as they are, even if those terms contain
+/// variables of the same name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Substitution {
+    bindings: HashMap<String, Term>,
+}
+
+impl Substitution {
+    /// Creates an empty substitution
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Computes the most general unifier of two terms
+    pub fn mgu(a: &Term, b: &Term) -> Option<Substitution> {
+        Substitution::new().unify_terms(a, b)
+    }
+
+    /// Returns the number of bound variables
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// Returns true if no variable is bound
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Returns the term bound to a variable
+    pub fn get(&self, var: &str) -> Option<&Term> {
+        self.bindings.get(var)
+    }
+
+    /// Binds a variable to a term, replacing any previous binding
+    ///
+    /// No consistency check is made; use the unification and matching
+    /// methods to extend a substitution safely.
+    pub fn bind(&mut self, var: &str, term: Term) {
+        self.bindings.insert(var.to_string(), term);
+    }
+
+    /// Iterates over the bindings in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Term)> {
+        self.bindings.iter().map(|(var, term)| (var.as_str(), term))
+    }
+
+    /// Applies the substitution to a term
+    ///
+    /// Variables quantified by a nested formula are local to it and are not
+    /// replaced inside that formula.
+    pub fn apply_term(&self, term: &Term) -> Term {
+        self.apply_scoped(term, &HashSet::new())
+    }
+
+    /// Applies the substitution to the three terms of a statement
+    pub fn apply_statement(&self, statement: &Statement) -> Statement {
+        self.apply_statement_scoped(statement, &HashSet::new())
+    }
+
+    /// Applies the substitution to every statement of a formula
+    ///
+    /// Quantified variables of the formula itself are renamed when bound to
+    /// another variable and dropped from the quantifier lists when bound to
+    /// anything else. Nested formulas keep their own quantified variables.
+    pub fn apply_formula(&self, formula: &Formula) -> Formula {
+        let mut result = Formula::new();
+        for statement in &formula.statements {
+            result.add_statement(self.apply_statement(statement));
+        }
+
+        let requantify = |vars: &HashSet<String>| -> HashSet<String> {
+            vars.iter()
+                .filter_map(|var| match self.bindings.get(var) {
+                    None => Some(var.clone()),
+                    Some(Term::Variable(renamed)) => Some(renamed.clone()),
+                    Some(_) => None,
+                })
+                .collect()
+        };
+        result.universal_vars = requantify(&formula.universal_vars);
+        result.existential_vars = requantify(&formula.existential_vars);
+        result
+    }
+
+    /// Returns the substitution that applies `self` and then `other`
+    pub fn compose(&self, other: &Substitution) -> Substitution {
+        let mut bindings: HashMap<String, Term> = self
+            .bindings
+            .iter()
+            .map(|(var, term)| (var.clone(), other.apply_term(term)))
+            .collect();
+        for (var, term) in &other.bindings {
+            bindings.entry(var.clone()).or_insert_with(|| term.clone());
+        }
+        bindings.retain(|var, term| !matches!(term, Term::Variable(name) if name == var));
+
+        Substitution { bindings }
+    }
+
+    /// Extends this substitution to a most general unifier of two terms
+    ///
+    /// Variables on both sides may be bound. Lists unify element by element
+    /// and nested formulas unify when their statements can be paired up one
+    /// to one; if several pairings work, the first one found is used.
+    /// Returns `None` if the terms cannot be unified.
+    pub fn unify_terms(&self, a: &Term, b: &Term) -> Option<Substitution> {
+        let mut result = self.clone();
+        result.unify_into(a, b).then_some(result)
+    }
+
+    /// Extends this substitution to a most general unifier of two statements
+    pub fn unify_statements(&self, a: &Statement, b: &Statement) -> Option<Substitution> {
+        self.unify_terms(&a.subject, &b.subject)?
+            .unify_terms(&a.predicate, &b.predicate)?
+            .unify_terms(&a.object, &b.object)
+    }
+
+    /// Extends this substitution to a unifier of two formulas
+    ///
+    /// The formulas must have the same number of statements and quantified
+    /// variables, and every statement of `a` must unify with a distinct
+    /// statement of `b`.
+    pub fn unify_formulas(&self, a: &Formula, b: &Formula) -> Option<Substitution> {
+        self.pair_statements(a, b, Mode::Unify)
+    }
+
+    /// Extends this substitution so that `pattern` becomes equal to `target`
+    ///
+    /// Only variables of the pattern are bound; variables in the target are
+    /// treated like any other term. Returns `None` if the pattern does not
+    /// match.
+    pub fn match_term(&self, pattern: &Term, target: &Term) -> Option<Substitution> {
+        let mut result = self.clone();
+        result.match_into(pattern, target).then_some(result)
+    }
+
+    /// Extends this substitution so that the statement `pattern` becomes equal to `target`
+    pub fn match_statement(&self, pattern: &Statement, target: &Statement) -> Option<Substitution> {
+        self.match_term(&pattern.subject, &target.subject)?
+            .match_term(&pattern.predicate, &target.predicate)?
+            .match_term(&pattern.object, &target.object)
+    }
+
+    /// Follows bindings until reaching an unbound variable or a non-variable term
+    fn walk<'a>(&'a self, mut term: &'a Term) -> &'a Term {
+        while let Term::Variable(var) = term {
+            match self.bindings.get(var) {
+                Some(bound) if bound != term => term = bound,
+                _ => break,
+            }
+        }
+        term
+    }
+
+    fn unify_into(&mut self, a: &Term, b: &Term) -> bool {
+        let a = self.walk(a).clone();
+        let b = self.walk(b).clone();
+        match (&a, &b) {
+            (Term::Variable(x), Term::Variable(y)) if x == y => true,
+            (Term::Variable(var), other) | (other, Term::Variable(var)) => {
+                let other = self.apply_term(other);
+                if occurs(var, &other) {
+                    return false;
+                }
+                self.bind_resolved(var, other);
+                true
+            }
+            (Term::List(xs), Term::List(ys)) => {
+                xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| self.unify_into(x, y))
+            }
+            (Term::Formula(x), Term::Formula(y)) => match self.pair_statements(x, y, Mode::Unify) {
+                Some(result) => {
+                    *self = result;
+                    true
+                }
+                None => false,
+            },
+            _ => a == b,
+        }
+    }
+
+    /// Binds a variable to a term that is already resolved, keeping the substitution idempotent
+    fn bind_resolved(&mut self, var: &str, term: Term) {
+        let single = Substitution { bindings: HashMap::from([(var.to_string(), term.clone())]) };
+        for bound in self.bindings.values_mut() {
+            *bound = single.apply_term(bound);
+        }
+        self.bindings.insert(var.to_string(), term);
+    }
+
+    fn match_into(&mut self, pattern: &Term, target: &Term) -> bool {
+        match (pattern, target) {
+            (Term::Variable(var), _) => match self.bindings.get(var) {
+                Some(bound) => bound == target,
+                None => {
+                    self.bindings.insert(var.clone(), target.clone());
+                    true
+                }
+            },
+            (Term::List(xs), Term::List(ys)) => {
+                xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| self.match_into(x, y))
+            }
+            (Term::Formula(x), Term::Formula(y)) => match self.pair_statements(x, y, Mode::Match) {
+                Some(result) => {
+                    *self = result;
+                    true
+                }
+                None => false,
+            },
+            _ => pattern == target,
+        }
+    }
+
+    /// Finds a one-to-one pairing of the statements of two formulas under which they unify or match
+    fn pair_statements(&self, a: &Formula, b: &Formula, mode: Mode) -> Option<Substitution> {
+        if a.statements.len() != b.statements.len()
+            || a.universal_vars.len() != b.universal_vars.len()
+            || a.existential_vars.len() != b.existential_vars.len()
+        {
+            return None;
+        }
+
+        let mut used = vec![false; b.statements.len()];
+        self.pair_from(a.statements.as_slice(), b.statements.as_slice(), &mut used, mode)
+    }
+
+    fn pair_from(&self, rest: &[Statement], candidates: &[Statement], used: &mut [bool], mode: Mode) -> Option<Substitution> {
+        let Some((first, rest)) = rest.split_first() else {
+            return Some(self.clone());
+        };
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            if used[index] {
+                continue;
+            }
+            let extended = match mode {
+                Mode::Unify => self.unify_statements(first, candidate),
+                Mode::Match => self.match_statement(first, candidate),
+            };
+            if let Some(extended) = extended {
+                used[index] = true;
+                let paired = extended.pair_from(rest, candidates, used, mode);
+                used[index] = false;
+                if paired.is_some() {
+                    return paired;
+                }
+            }
+        }
+        None
+    }
+
+    fn apply_scoped(&self, term: &Term, local: &HashSet<String>) -> Term {
+        match term {
+            Term::Variable(var) if !local.contains(var) => match self.bindings.get(var) {
+                Some(bound) => bound.clone(),
+                None => term.clone(),
+            },
+            Term::List(items) => Term::List(items.iter().map(|item| self.apply_scoped(item, local)).collect()),
+            Term::Formula(formula) => {
+                let mut local = local.clone();
+                local.extend(formula.universal_vars.iter().cloned());
+                local.extend(formula.existential_vars.iter().cloned());
+
+                let mut result = Formula::new();
+                for statement in &formula.statements {
+                    result.add_statement(self.apply_statement_scoped(statement, &local));
+                }
+                result.universal_vars = formula.universal_vars.clone();
+                result.existential_vars = formula.existential_vars.clone();
+                Term::Formula(Arc::new(result))
+            }
+            _ => term.clone(),
+        }
+    }
+
+    fn apply_statement_scoped(&self, statement: &Statement, local: &HashSet<String>) -> Statement {
+        Statement {
+            subject: self.apply_scoped(&statement.subject, local),
+            predicate: self.apply_scoped(&statement.predicate, local),
+            object: self.apply_scoped(&statement.object, local),
+        }
+    }
+}
+
+/// Whether formula statements are unified both ways or matched one way
+#[derive(Clone, Copy)]
+enum Mode {
+    Unify,
+    Match,
+}
+
+/// Whether a variable occurs anywhere inside a term
+fn occurs(var: &str, term: &Term) -> bool {
+    match term {
+        Term::Variable(name) => name == var,
+        Term::List(items) => items.iter().any(|item| occurs(var, item)),
+        Term::Formula(formula) => formula.statements.iter().any(|statement| {
+            occurs(var, &statement.subject) || occurs(var, &statement.predicate) || occurs(var, &statement.object)
+        }),
+        _ => false,
+    }
+}
+
+impl fmt::Display for Substitution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sorted: BTreeMap<&String, &Term> = self.bindings.iter().collect();
+        write!(f, "{{")?;
+        for (i, (var, term)) in sorted.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "?{} -> {}", var, term)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl FromIterator<(String, Term)> for Substitution {
+    fn from_iter<I: IntoIterator<Item = (String, Term)>>(iter: I) -> Self {
+        Substitution { bindings: iter.into_iter().collect() }
+    }
+}
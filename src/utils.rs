@@ -42,21 +42,9 @@ pub fn create_triple(subject: Term, predicate: Term, object: Term) -> Statement
 }
 
 /// Helper function to check if two Formulas are equivalent
-/// 
-/// This is a simplistic implementation that just checks if they have the same statements.
-/// A more complete implementation would check for semantic equivalence.
+///
+/// Formulas are equivalent when they contain the same statements up to a
+/// renaming of blank nodes and variables, including inside nested formulas.
 pub fn formulas_equivalent(a: &Formula, b: &Formula) -> bool {
-    if a.statements.len() != b.statements.len() {
-        return false;
-    }
-    
-    // This is a very basic check - a real implementation would be more sophisticated
-    // and would account for blank node identifiers, variable renaming, etc.
-    for stmt_a in &a.statements {
-        if !b.statements.contains(stmt_a) {
-            return false;
-        }
-    }
-    
-    true
-} 
\ No newline at end of file
+    a.is_isomorphic(b)
+}
@@ -0,0 +1,31 @@
+// Well-known IRIs used throughout the library
+
+pub(crate) const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub(crate) const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+pub(crate) const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+pub(crate) const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+pub(crate) const RDF_LIST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#List";
+
+pub(crate) const OWL_SAME_AS: &str = "http://www.w3.org/2002/07/owl#sameAs";
+
+pub(crate) const LOG_IMPLIES: &str = "http://www.w3.org/2000/10/swap/log#implies";
+pub(crate) const LOG_IS_IMPLIED_BY: &str = "http://www.w3.org/2000/10/swap/log#isImpliedBy";
+pub(crate) const LOG_QUERY: &str = "http://www.w3.org/2000/10/swap/log#query";
+
+pub(crate) const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+pub(crate) const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+pub(crate) const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+pub(crate) const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+pub(crate) const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+pub(crate) const XSD_FLOAT: &str = "http://www.w3.org/2001/XMLSchema#float";
+pub(crate) const XSD_DATE_TIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+pub(crate) const XSD_DATE: &str = "http://www.w3.org/2001/XMLSchema#date";
+pub(crate) const XSD_DURATION: &str = "http://www.w3.org/2001/XMLSchema#duration";
+
+// Namespaces of the standard builtins, which their local names are appended to
+
+pub(crate) const MATH: &str = "http://www.w3.org/2000/10/swap/math#";
+pub(crate) const STRING: &str = "http://www.w3.org/2000/10/swap/string#";
+pub(crate) const LIST: &str = "http://www.w3.org/2000/10/swap/list#";
+pub(crate) const LOG: &str = "http://www.w3.org/2000/10/swap/log#";
+pub(crate) const TIME: &str = "http://www.w3.org/2000/10/swap/time#";
@@ -0,0 +1,84 @@
+use n3proof::{
+    create_proof_engine, parse_n3, Builtin, BuiltinError, Error, Formula, ParseOptions, Program, ProofOutcome,
+    Statement, Substitution, Term,
+};
+use oxrdf::{Literal, NamedNode};
+
+const DOUBLE: &str = "http://example.org/double";
+
+fn parse(input: &str) -> Formula {
+    let document = format!("@prefix : <http://example.org/> .\n{}", input);
+    parse_n3(document.as_bytes(), ParseOptions::default()).expect("document should parse")
+}
+
+fn ex(local: &str) -> Term {
+    Term::Iri(NamedNode::new(format!("http://example.org/{}", local)).unwrap())
+}
+
+fn integer(value: i64) -> Term {
+    Term::Literal(Literal::new_typed_literal(value.to_string(), NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#integer")))
+}
+
+/// `?x :double ?y` holds when `?y` is twice the integer `?x`
+struct Double;
+
+impl Builtin for Double {
+    fn evaluate(&self, subject: &Term, object: &Term, binding: &Substitution) -> Result<Vec<Substitution>, BuiltinError> {
+        let value = match subject {
+            Term::Variable(_) => return Err(BuiltinError::insufficiently_bound(DOUBLE)),
+            Term::Literal(literal) => literal
+                .value()
+                .parse::<i64>()
+                .map_err(|_| BuiltinError::invalid_argument(DOUBLE, "expected an integer"))?,
+            _ => return Err(BuiltinError::invalid_argument(DOUBLE, "expected an integer")),
+        };
+        Ok(binding.unify_terms(object, &integer(value * 2)).into_iter().collect())
+    }
+}
+
+fn doubling_engine(rules: &str) -> n3proof::ProofEngine {
+    let mut engine = create_proof_engine();
+    engine.register_builtin(DOUBLE, Double);
+    engine.add_program(&Program::from_formula(&parse(&format!(":a :value 21 .\n{}", rules))));
+    engine
+}
+
+#[test]
+fn test_forward_rules_evaluate_builtins() {
+    // The builtin comes first but is only evaluated once ?v is bound
+    let mut engine = doubling_engine("{ ?v :double ?d . ?x :value ?v } => { ?x :doubled ?d } .");
+    assert!(engine.builtins().contains(DOUBLE));
+
+    let derived = engine.saturate().unwrap();
+    assert!(derived.contains(&Statement { subject: ex("a"), predicate: ex("doubled"), object: integer(42) }));
+    assert_eq!(derived.len(), 1);
+}
+
+#[test]
+fn test_backward_rules_evaluate_builtins() {
+    let mut engine = doubling_engine("{ ?x :doubled ?d } <= { ?x :value ?v . ?v :double ?d } .");
+    engine.set_goal(parse(":a :doubled 42 ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::Proven(_)));
+
+    engine.set_goal(parse(":a :doubled 43 ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::NotProvable));
+
+    // Builtins can also appear in the goal itself
+    engine.set_goal(parse("21 :double ?d ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::Proven(_)));
+}
+
+#[test]
+fn test_closures_are_builtins_and_errors_propagate() {
+    let mut engine = doubling_engine("{ ?x :value ?v . ?v :check true } => { ?x :checked true } .");
+    engine.register_builtin("http://example.org/check", |_: &Term, _: &Term, _: &Substitution| {
+        Err(BuiltinError::invalid_argument("http://example.org/check", "always fails"))
+    });
+
+    match engine.saturate() {
+        Err(Error::BuiltinError(BuiltinError::InvalidArgument { builtin, .. })) => {
+            assert_eq!(builtin, "http://example.org/check");
+        }
+        other => panic!("expected a builtin error, got {:?}", other),
+    }
+}
@@ -0,0 +1,92 @@
+// Helpers shared by the builtin tests, which derive `:result :is ?r` from a rule premise
+#![allow(dead_code)]
+
+use n3proof::{create_proof_engine, parse_n3, Formula, ParseOptions, Program, ProofEngine, Statement, Term};
+use oxrdf::{Literal, NamedNode};
+
+/// Prefixes every fixture declares, before those of the namespaces under test
+const COMMON_PREFIXES: &str = "@prefix : <http://example.org/> .\n\
+                               @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n";
+
+/// Parses and runs documents that use the prefixes of one test file
+pub struct Fixture {
+    prefixes: &'static str,
+}
+
+impl Fixture {
+    /// Creates a fixture whose documents declare `prefixes` besides `:` and `xsd:`
+    pub const fn new(prefixes: &'static str) -> Self {
+        Fixture { prefixes }
+    }
+
+    pub fn parse(&self, input: &str) -> Formula {
+        let document = format!("{}{}{}", COMMON_PREFIXES, self.prefixes, input);
+        parse_n3(document.as_bytes(), ParseOptions::default()).expect("document should parse")
+    }
+
+    pub fn statement(&self, input: &str) -> Statement {
+        self.parse(input).statements.iter().next().expect("one statement").clone()
+    }
+
+    /// The object of a single-statement document, e.g. the list in `:x :is (1 2)`
+    pub fn term(&self, value: &str) -> Term {
+        self.statement(&format!(":x :is {} .", value)).object
+    }
+
+    pub fn engine(&self, document: &str) -> ProofEngine {
+        let mut engine = create_proof_engine();
+        engine.add_program(&Program::from_formula(&self.parse(document)));
+        engine
+    }
+
+    /// Saturates `document` and returns the objects derived for `:result :is ?r`
+    pub fn results(&self, document: &str) -> Vec<Term> {
+        derived_results(&mut self.engine(document))
+    }
+
+    /// Saturates a rule concluding `:result :is <result>` from `premise`, returning the values of `<result>`
+    pub fn derive(&self, premise: &str, result: &str) -> Vec<Term> {
+        self.results(&format!("{{ {} }} => {{ :result :is {} }} .", premise, result))
+    }
+
+    /// The values of `?r` derived from `premise`
+    pub fn values(&self, premise: &str) -> Vec<Term> {
+        self.derive(premise, "?r")
+    }
+
+    /// The single value of `?r` derived from `premise`
+    pub fn result(&self, premise: &str) -> Term {
+        let values = self.derive(premise, "?r");
+        assert_eq!(values.len(), 1, "expected one result for {}", premise);
+        values[0].clone()
+    }
+
+    /// Whether a rule with `premise` fires
+    pub fn holds(&self, premise: &str) -> bool {
+        !self.derive(premise, "true").is_empty()
+    }
+}
+
+/// Saturates `engine` and returns the objects derived for `:result :is ?r`
+pub fn derived_results(engine: &mut ProofEngine) -> Vec<Term> {
+    let derived = engine.saturate().expect("saturation should succeed");
+    let is = Term::Iri(NamedNode::new("http://example.org/is").unwrap());
+    derived.matching(None, Some(&is), None).map(|statement| statement.object.clone()).collect()
+}
+
+/// Sorts terms by their debug form, for results derived in no particular order
+pub fn sorted(mut terms: Vec<Term>) -> Vec<Term> {
+    terms.sort_by_key(|term| format!("{:?}", term));
+    terms
+}
+
+pub fn typed(value: &str, datatype: &str) -> Term {
+    Term::Literal(Literal::new_typed_literal(
+        value,
+        NamedNode::new(format!("http://www.w3.org/2001/XMLSchema#{}", datatype)).unwrap(),
+    ))
+}
+
+pub fn plain(value: &str) -> Term {
+    Term::Literal(Literal::new_simple_literal(value))
+}
@@ -3,14 +3,12 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str;
-use std::io::Write;
 
 use n3proof::{
     create_proof_engine,
     parse_n3,
-    Formula, Rule, Statement, Term, Proof, ParseOptions
+    ParseOptions, Program, ProofOutcome
 };
-use oxrdf::{NamedNode, Literal};
 
 /// Integration test for discovering and validating proofs from the EYE reasoner
 #[test]
@@ -68,7 +66,7 @@ fn setup_eye_repository() -> PathBuf {
         
         println!("Cloning EYE repository...");
         let clone_result = Command::new("git")
-            .args(&["clone", "https://github.com/eyereasoner/eye.git", "."])
+            .args(["clone", "https://github.com/eyereasoner/eye.git", "."])
             .current_dir(&temp_dir)
             .output();
         
@@ -143,111 +141,49 @@ fn validate_eye_proof(proof_path: &Path) -> Result<bool, Box<dyn std::error::Err
     // 1. Parse the N3 file
     let proof_content = fs::read_to_string(proof_path)?;
     
-    // Parse using our custom N3 parser since the library parser is not yet fully implemented
     println!("Validating proof from file: {}", proof_path.display());
     println!("Proof size: {} bytes", proof_content.len());
     
-    // Create a simple custom parser for N3 content
-    let proof_formula = simple_n3_parser(&proof_content);
+    let proof_formula = parse_n3(proof_content.as_bytes(), ParseOptions::default())?;
     
     println!("  Parsed formula with {} statements", proof_formula.statements.len());
     
-    // 2. Create a proof engine to validate the proof
-    let mut engine = create_proof_engine();
-    
-    // 3. Extract axioms, rules, and inferences from the formula
-    let (axioms, rules) = extract_axioms_and_rules(&proof_formula);
-    
-    println!("  Extracted {} axioms and {} rules", axioms.len(), rules.len());
-    
-    // 4. Add axioms to the engine
-    for axiom in axioms {
-        engine.add_axiom(axiom);
-    }
+    // 2. Split the document into facts and rules
+    let mut program = Program::from_formula(&proof_formula);
     
-    // 5. Add rules to the engine
-    for rule in rules {
-        engine.add_rule(rule);
-    }
+    println!("  Extracted {} facts and {} rules", program.facts.statements.len(), program.rules().count());
     
-    // Set a simple rule to make the test pass
+    // 3. The mock proof concludes that Socrates is mortal, which must follow
+    //    from the other facts and the rule without being stated
     if proof_path.file_name().unwrap_or_default() == "mock_proof.n3" {
-        // Add a simple rule for the mock proof
-        let human = Term::Iri(NamedNode::new("http://example.org/socrates#Human").unwrap());
-        let subclassof = Term::Iri(NamedNode::new("http://www.w3.org/2000/01/rdf-schema#subClassOf").unwrap());
-        let mortal = Term::Iri(NamedNode::new("http://example.org/socrates#Mortal").unwrap());
-        let type_pred = Term::Iri(NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap());
-        let socrates = Term::Iri(NamedNode::new("http://example.org/socrates#Socrates").unwrap());
-        
-        // Premise: If X is a Human and Human is a subclass of Mortal, then X is a Mortal
-        let mut premise_formula = Formula::new();
-        let x_var = Term::Variable("X".to_string());
-        
-        let x_is_human = Statement {
-            subject: x_var.clone(),
-            predicate: type_pred.clone(),
-            object: human.clone(),
-        };
-        
-        let human_subclass_mortal = Statement {
-            subject: human.clone(),
-            predicate: subclassof.clone(),
-            object: mortal.clone(),
-        };
-        
-        premise_formula.add_statement(x_is_human);
-        premise_formula.add_statement(human_subclass_mortal);
-        premise_formula.add_universal_var("X");
-        
-        // Conclusion: X is Mortal
-        let mut conclusion_formula = Formula::new();
-        let x_is_mortal = Statement {
-            subject: x_var.clone(),
-            predicate: type_pred.clone(),
-            object: mortal.clone(),
-        };
-        
-        conclusion_formula.add_statement(x_is_mortal);
-        conclusion_formula.add_universal_var("X");
-        
-        let rule = Rule::new(
-            "subclass_rule", 
-            vec![premise_formula],
-            conclusion_formula
-        ).with_description("If X is A and A is a subclass of B, then X is B");
-        
-        let rule_idx = engine.add_rule(rule);
-        
-        // Add fact that Socrates is Human
-        let mut socrates_formula = Formula::new();
-        let socrates_is_human = Statement {
-            subject: socrates.clone(),
-            predicate: type_pred.clone(),
-            object: human.clone(),
-        };
-        socrates_formula.add_statement(socrates_is_human);
-        
-        let socrates_idx = engine.add_axiom(socrates_formula);
-        
-        // Apply the rule
-        match engine.apply_rule(rule_idx, &[socrates_idx]) {
-            Ok(_) => println!("  Applied rule to derive Socrates is Mortal"),
-            Err(e) => println!("  Failed to apply rule: {}", e),
+        let socrates_is_mortal = parse_n3(
+            "<http://example.org/socrates#Socrates> a <http://example.org/socrates#Mortal> .".as_bytes(),
+            ParseOptions::default(),
+        )?;
+        for statement in &socrates_is_mortal.statements {
+            program.facts.statements.remove(statement);
         }
+        
+        let mut engine = create_proof_engine();
+        engine.add_program(&program);
+        engine.set_goal(socrates_is_mortal);
+        let proven = matches!(engine.prove()?, ProofOutcome::Proven(_));
+        println!("  Proof validation result: {}", if proven { "valid" } else { "invalid" });
+        return Ok(proven);
     }
     
-    // 6. Get the proof object
+    // 4. Load the program and derive everything the rules allow
+    let mut engine = create_proof_engine();
+    engine.add_program(&program);
+    let derived = engine.saturate()?;
+    println!("  Derived {} statements", derived.len());
+    
+    // 5. Get the proof object
     let proof = engine.get_proof();
     
     println!("  Proof has {} steps", proof.steps.len());
     
-    // 7. For the mock proof, consider it valid if the proof has steps
-    if proof_path.file_name().unwrap_or_default() == "mock_proof.n3" && !proof.steps.is_empty() {
-        println!("  Proof validation result: valid (mock proof special case)");
-        return Ok(true);
-    }
-    
-    // 8. For other proofs, check if the proof is valid using the library's validation
+    // 6. Check that the proof is valid using the library's validation
     match proof.is_valid() {
         Ok(is_valid) => {
             println!("  Proof validation result: {}", if is_valid { "valid" } else { "invalid" });
@@ -259,211 +195,3 @@ fn validate_eye_proof(proof_path: &Path) -> Result<bool, Box<dyn std::error::Err
         },
     }
 }
-
-/// A very simple N3 parser that extracts some triples from N3 content
-/// This is just for testing purposes since the library's parser is not yet fully implemented
-fn simple_n3_parser(content: &str) -> Formula {
-    let mut formula = Formula::new();
-    
-    // Define some common prefixes
-    let mut prefixes = std::collections::HashMap::new();
-    prefixes.insert("rdfs".to_string(), "http://www.w3.org/2000/01/rdf-schema#".to_string());
-    prefixes.insert("rdf".to_string(), "http://www.w3.org/1999/02/22-rdf-syntax-ns#".to_string());
-    prefixes.insert("log".to_string(), "http://www.w3.org/2000/10/swap/log#".to_string());
-    
-    // Look for prefix declarations
-    for line in content.lines() {
-        if line.starts_with("@prefix") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 {
-                let prefix = parts[1].trim_end_matches(':');
-                let mut uri = parts[2].to_string();
-                // Remove < > and trailing .
-                uri = uri.trim_start_matches('<').trim_end_matches('>').trim_end_matches('.').to_string();
-                prefixes.insert(prefix.to_string(), uri);
-            }
-        }
-    }
-    
-    // Extract simple triple statements (subject predicate object .)
-    for line in content.lines() {
-        let line = line.trim();
-        
-        // Skip comments and empty lines
-        if line.starts_with('#') || line.is_empty() {
-            continue;
-        }
-        
-        // Skip lines that look like they're part of a rule
-        if line.contains("=>") || line.contains("{") || line.contains("}") {
-            continue;
-        }
-        
-        // Try to extract simple triples
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 4 && parts[parts.len() - 1].ends_with('.') {
-            if let (Some(subject), Some(predicate), Some(object)) = (
-                parse_n3_term(parts[0], &prefixes),
-                parse_n3_term(parts[1], &prefixes),
-                parse_n3_term(parts[2], &prefixes)
-            ) {
-                formula.add_statement(Statement {
-                    subject,
-                    predicate,
-                    object,
-                });
-            }
-        }
-    }
-    
-    // For the mock proof specifically, add some statements we know should be there
-    if content.contains(":Socrates a :Human") && content.contains(":Human rdfs:subClassOf :Mortal") {
-        // These are the statements from our mock proof
-        let socrates = Term::Iri(NamedNode::new("http://example.org/socrates#Socrates").unwrap());
-        let human = Term::Iri(NamedNode::new("http://example.org/socrates#Human").unwrap());
-        let mortal = Term::Iri(NamedNode::new("http://example.org/socrates#Mortal").unwrap());
-        let type_pred = Term::Iri(NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap());
-        let subclassof = Term::Iri(NamedNode::new("http://www.w3.org/2000/01/rdf-schema#subClassOf").unwrap());
-        
-        // Socrates is a Human
-        formula.add_statement(Statement {
-            subject: socrates.clone(),
-            predicate: type_pred.clone(),
-            object: human.clone(),
-        });
-        
-        // Human is a subclass of Mortal
-        formula.add_statement(Statement {
-            subject: human.clone(),
-            predicate: subclassof.clone(),
-            object: mortal.clone(),
-        });
-        
-        // Socrates is Mortal (the derived statement)
-        formula.add_statement(Statement {
-            subject: socrates.clone(),
-            predicate: type_pred.clone(),
-            object: mortal.clone(),
-        });
-    }
-    
-    formula
-}
-
-/// Parse an N3 term using known prefixes
-fn parse_n3_term(term: &str, prefixes: &std::collections::HashMap<String, String>) -> Option<Term> {
-    if term.starts_with('<') && term.ends_with('>') {
-        // IRI
-        let iri = term.trim_start_matches('<').trim_end_matches('>');
-        NamedNode::new(iri).ok().map(Term::Iri)
-    } else if term.starts_with('\"') && term.ends_with('\"') {
-        // Literal
-        let literal_str = term.trim_start_matches('\"').trim_end_matches('\"');
-        Some(Term::Literal(Literal::new_simple_literal(literal_str)))
-    } else if term.starts_with('?') {
-        // Variable
-        Some(Term::Variable(term[1..].to_string()))
-    } else if term.contains(':') {
-        // Prefixed name
-        let parts: Vec<&str> = term.split(':').collect();
-        if parts.len() == 2 {
-            let prefix = parts[0];
-            let local = parts[1];
-            if let Some(uri_base) = prefixes.get(prefix) {
-                let full_uri = format!("{}{}", uri_base, local);
-                NamedNode::new(&full_uri).ok().map(Term::Iri)
-            } else {
-                // Default namespace (common in examples)
-                let full_uri = format!("http://example.org/{}{}", prefix, local);
-                NamedNode::new(&full_uri).ok().map(Term::Iri)
-            }
-        } else {
-            None
-        }
-    } else if term == "a" {
-        // 'a' is shorthand for rdf:type
-        NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").ok().map(Term::Iri)
-    } else {
-        None
-    }
-}
-
-/// Extracts axioms and rules from a formula
-fn extract_axioms_and_rules(formula: &Formula) -> (Vec<Formula>, Vec<Rule>) {
-    let mut axioms = Vec::new();
-    let mut rules = Vec::new();
-    
-    // This is a simplified implementation that assumes a particular structure
-    // In reality, you'd need to analyze the N3 statements to properly identify
-    // axioms and rules according to the N3 semantics
-    
-    // For now, treat all non-implication statements as axioms
-    let mut axiom_formula = Formula::new();
-    for statement in &formula.statements {
-        // Add any statements that don't look like rules
-        // (this is a simplification)
-        if !is_rule_statement(statement) {
-            axiom_formula.add_statement(statement.clone());
-        }
-    }
-    
-    if !axiom_formula.statements.is_empty() {
-        axioms.push(axiom_formula);
-    }
-    
-    // Create simple rules from implications
-    // This is highly simplified and should be expanded
-    for statement in &formula.statements {
-        if let Some(rule) = try_create_rule(statement) {
-            rules.push(rule);
-        }
-    }
-    
-    (axioms, rules)
-}
-
-/// Check if a statement appears to represent a rule
-fn is_rule_statement(statement: &Statement) -> bool {
-    // This is a simplified check - in real code, you'd need to properly
-    // analyze N3 implication syntax
-    
-    // Check if the predicate might be an implication
-    match &statement.predicate {
-        Term::Iri(iri) => {
-            let iri_str = iri.as_str();
-            iri_str.contains("implies") || 
-            iri_str.contains("log:implies") || 
-            iri_str.contains("=>")
-        },
-        _ => false,
-    }
-}
-
-/// Try to create a rule from a statement
-fn try_create_rule(statement: &Statement) -> Option<Rule> {
-    // This is a simplified implementation - a real implementation would
-    // need to properly parse the N3 rule syntax
-    
-    if is_rule_statement(statement) {
-        // Create premise formula
-        let mut premise = Formula::new();
-        premise.add_statement(Statement {
-            subject: statement.subject.clone(),
-            predicate: Term::Iri(oxrdf::NamedNode::new("http://example.org/premise").unwrap()),
-            object: Term::Iri(oxrdf::NamedNode::new("http://example.org/value").unwrap()),
-        });
-        
-        // Create conclusion formula
-        let mut conclusion = Formula::new();
-        conclusion.add_statement(Statement {
-            subject: statement.object.clone(),
-            predicate: Term::Iri(oxrdf::NamedNode::new("http://example.org/conclusion").unwrap()),
-            object: Term::Iri(oxrdf::NamedNode::new("http://example.org/value").unwrap()),
-        });
-        
-        // Create rule
-        Some(Rule::new("extracted_rule", vec![premise], conclusion))
-    } else {
-        None
-    }
-} 
\ No newline at end of file
@@ -0,0 +1,50 @@
+use n3proof::{create_proof_engine, parse_n3, IdTriple, Interner, ParseOptions, Statement, Term, TripleStore};
+use oxrdf::NamedNode;
+
+fn ex(local: &str) -> Term {
+    Term::Iri(NamedNode::new(format!("http://example.org/{}", local)).unwrap())
+}
+
+#[test]
+fn test_interning_round_trip() {
+    let formula = parse_n3(
+        "@prefix : <http://example.org/> .\n\
+         :a :p :b . :b :p :c . :c :p \"lit\"@en . :a :q { :a :p :b } .\n"
+            .as_bytes(),
+        ParseOptions::default(),
+    )
+    .unwrap();
+
+    let mut interner = Interner::new();
+    let mut store: TripleStore<IdTriple> = TripleStore::new();
+    for statement in &formula.statements {
+        store.insert(interner.intern_statement(statement));
+    }
+
+    // :a :b :c :p :q the literal and the quoted formula
+    assert_eq!(interner.len(), 7);
+    assert_eq!(interner.intern(&ex("a")), interner.get(&ex("a")).unwrap());
+    assert_eq!(interner.resolve(interner.get(&ex("q")).unwrap()), &ex("q"));
+    assert_eq!(interner.resolve_store(&store), formula);
+
+    let p = interner.get(&ex("p")).unwrap();
+    assert_eq!(store.matching(None, Some(&p), None).count(), 3);
+    assert!(interner.get(&ex("unknown")).is_none());
+}
+
+#[test]
+fn test_engine_facts_are_interned() {
+    let mut engine = create_proof_engine();
+    let mut axiom = n3proof::Formula::new();
+    for i in 0..100 {
+        axiom.add_statement(Statement { subject: ex(&format!("s{}", i)), predicate: ex("type"), object: ex("Thing") });
+    }
+    engine.add_axiom(axiom.clone());
+    engine.add_axiom(axiom.clone());
+
+    assert_eq!(engine.fact_count(), 100);
+    assert_eq!(engine.interner().len(), 102);
+    assert!(engine.contains_fact(&Statement { subject: ex("s7"), predicate: ex("type"), object: ex("Thing") }));
+    assert!(!engine.contains_fact(&Statement { subject: ex("s7"), predicate: ex("type"), object: ex("Other") }));
+    assert_eq!(engine.facts(), axiom);
+}
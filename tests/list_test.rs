@@ -0,0 +1,70 @@
+mod common;
+
+use common::{sorted, Fixture};
+use n3proof::{ProofOutcome, Statement, Term};
+
+const LIST: Fixture = Fixture::new(
+    "@prefix list: <http://www.w3.org/2000/10/swap/list#> .\n\
+     @prefix math: <http://www.w3.org/2000/10/swap/math#> .\n",
+);
+
+/// The values of `<result>` derived from `premise`, sorted
+fn results(premise: &str, result: &str) -> Vec<Term> {
+    sorted(LIST.derive(premise, result))
+}
+
+#[test]
+fn test_list_functions() {
+    assert_eq!(results("((1 2) (3) ()) list:append ?r", "?r"), vec![LIST.term("(1 2 3)")]);
+    assert_eq!(results("(1 2 3) list:first ?r", "?r"), vec![LIST.term("1")]);
+    assert_eq!(results("(1 2 3) list:rest ?r", "?r"), vec![LIST.term("(2 3)")]);
+    assert_eq!(results("(1 2 3) list:last ?r", "?r"), vec![LIST.term("3")]);
+    assert_eq!(results("(1 2 3) list:length ?r", "?r"), vec![LIST.term("3")]);
+    assert_eq!(results("((1 2 1 3) 1) list:remove ?r", "?r"), vec![LIST.term("(2 3)")]);
+    assert_eq!(results("(3 :b 1.5 \"a\" :a 10) list:sort ?r", "?r"), vec![LIST.term("(1.5 3 10 \"a\" :a :b)")]);
+    // Strings that look like numbers are strings, ordered by code point
+    assert_eq!(
+        results("(\"b\" \"10\" \"a\" \"9\" 2) list:sort ?r", "?r"),
+        vec![LIST.term("(2 \"10\" \"9\" \"a\" \"b\")")]
+    );
+    assert_eq!(results("(1 2 1 3 2) list:unique ?r", "?r"), vec![LIST.term("(1 2 3)")]);
+    assert_eq!(results("((:a :b :c) 1) list:memberAt ?r", "?r"), vec![LIST.term(":b")]);
+    assert_eq!(results("((1 -2 3) math:negation) list:map ?r", "?r"), vec![LIST.term("(-1 2 -3)")]);
+    assert!(results("() list:first ?r", "?r").is_empty());
+}
+
+#[test]
+fn test_builtins_with_several_solutions() {
+    assert_eq!(results("(:a :b) list:member ?r", "?r"), vec![LIST.term(":a"), LIST.term(":b")]);
+    assert_eq!(results(":b list:in (:a :b)", "true"), vec![LIST.term("true")]);
+    assert_eq!(
+        results("(:a :b) list:iterate (?i ?x)", "(?i ?x)"),
+        vec![LIST.term("(0 :a)"), LIST.term("(1 :b)")]
+    );
+    assert_eq!(
+        results("((:a :b) ?i) list:memberAt ?x", "(?i ?x)"),
+        vec![LIST.term("(0 :a)"), LIST.term("(1 :b)")]
+    );
+
+    // With unbound parts, list:append enumerates every split of its object
+    assert_eq!(
+        results("(?front ?back) list:append (1 2)", "(?front ?back)"),
+        vec![LIST.term("((1 2) ())"), LIST.term("((1) (2))"), LIST.term("(() (1 2))")]
+    );
+    assert_eq!(results("((1) ?back) list:append (1 2 3)", "?back"), vec![LIST.term("(2 3)")]);
+}
+
+#[test]
+fn test_list_calls_are_recorded_as_evidence() {
+    let document = ":basket :items (:apple :pear) .\n\
+                    { ?b :contains ?x } <= { ?b :items ?items . ?items list:member ?x } .";
+    let mut engine = LIST.engine(document);
+    engine.set_goal(LIST.parse(":basket :contains :pear ."));
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("the goal should be proven");
+    };
+
+    let step = proof.steps.iter().find(|step| step.rule == "rule1").expect("the backward rule was used");
+    let member: Vec<Statement> = LIST.parse("(:apple :pear) list:member :pear .").statements.iter().cloned().collect();
+    assert_eq!(step.evidence, member);
+}
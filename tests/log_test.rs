@@ -0,0 +1,69 @@
+mod common;
+
+use common::{sorted, Fixture};
+use n3proof::Term;
+
+const LOG: Fixture = Fixture::new("@prefix log: <http://www.w3.org/2000/10/swap/log#> .\n");
+
+#[test]
+fn test_includes_and_not_includes() {
+    let document = ":doc :says { :alice :knows :bob . :bob :knows :carol } .\n\
+                    { :doc :says ?f . ?f log:includes { ?x :knows :carol } } => { :result :is ?x } .";
+    assert_eq!(LOG.results(document), vec![LOG.term(":bob")]);
+
+    assert!(LOG.holds("{ :a :b :c . :c :d :e } log:includes { :a :b [] }"));
+    assert!(LOG.holds("{ :a :b :c } log:includes {}"));
+    assert!(!LOG.holds("{ :a :b :c } log:includes { :a :b :d }"));
+    assert!(LOG.holds("{ :a :b :c } log:notIncludes { :a :b :d }"));
+    assert!(!LOG.holds("{ :a :b :c } log:notIncludes { ?x :b :c }"));
+}
+
+#[test]
+fn test_conjunction_and_equality() {
+    assert_eq!(LOG.values("({ :a :b :c } { :d :e :f . :a :b :c }) log:conjunction ?r"), vec![LOG.term("{ :a :b :c . :d :e :f }")]);
+    assert!(LOG.holds("{ :a :b :c } log:equalTo { :a :b :c }"));
+    assert!(LOG.holds(":a log:notEqualTo :b"));
+    assert!(!LOG.holds(":a log:notEqualTo :a"));
+    assert_eq!(LOG.values("(1 ?r) log:equalTo (?x 2)"), vec![LOG.term("2")]);
+}
+
+#[test]
+fn test_term_inspection() {
+    assert_eq!(LOG.values(":a log:uri ?r"), vec![LOG.term("\"http://example.org/a\"")]);
+    assert_eq!(LOG.values("?r log:uri \"http://example.org/b\""), vec![LOG.term(":b")]);
+    assert_eq!(LOG.values("{ :a :b :c } log:rawType ?r"), vec![LOG.term("log:Formula")]);
+    assert_eq!(LOG.values("\"x\" log:rawType ?r"), vec![LOG.term("log:Literal")]);
+    assert_eq!(LOG.values(":a log:rawType ?r"), vec![LOG.term("log:Other")]);
+    assert_eq!(LOG.values("(\"42\" xsd:integer) log:dtlit ?r"), vec![LOG.term("42")]);
+    assert_eq!(LOG.values("(?r ?t) log:dtlit 4.5"), vec![LOG.term("\"4.5\"")]);
+    assert_eq!(LOG.values("(\"chat\" \"fr\") log:langlit ?r"), vec![LOG.term("\"chat\"@fr")]);
+    assert_eq!(LOG.values("(?r ?l) log:langlit \"cat\"@en"), vec![LOG.term("\"cat\"")]);
+}
+
+#[test]
+fn test_skolem_is_deterministic() {
+    let skolems = LOG.values(":a log:skolem ?r");
+    assert_eq!(skolems.len(), 1);
+    assert!(matches!(&skolems[0], Term::Iri(iri) if iri.as_str().starts_with("urn:n3proof:skolem:")));
+    assert_eq!(LOG.values(":a log:skolem ?r"), skolems);
+    assert_ne!(LOG.values(":b log:skolem ?r"), skolems);
+
+    // Formulas that only differ by the names of their blank nodes get the same IRI
+    let formula_skolems = LOG.values("{ _:x :p _:y } log:skolem ?r");
+    assert_eq!(formula_skolems.len(), 1);
+    assert_eq!(LOG.values("{ _:u :p _:v } log:skolem ?r"), formula_skolems);
+}
+
+#[test]
+fn test_conclusion_reasons_over_a_quoted_formula() {
+    let document = ":theory :is { :a :parent :b . :b :parent :c .\n\
+                        { ?x :parent ?y } => { ?x :ancestor ?y } .\n\
+                        { ?x :parent ?y . ?y :ancestor ?z } => { ?x :ancestor ?z } } .\n\
+                    { :theory :is ?t . ?t log:conclusion ?c . ?c log:includes { :a :ancestor ?who } } => { :result :is ?who } .";
+    assert_eq!(sorted(LOG.results(document)), vec![LOG.term(":b"), LOG.term(":c")]);
+
+    // The conclusion is computed by a nested engine and does not leak out
+    let mut engine = LOG.engine(document);
+    engine.saturate().unwrap();
+    assert!(!engine.contains_fact(&LOG.statement(":a :ancestor :c .")));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{typed, Fixture};
+use n3proof::{ProofOutcome, Statement};
+
+const MATH: Fixture = Fixture::new("@prefix math: <http://www.w3.org/2000/10/swap/math#> .\n");
+
+#[test]
+fn test_arithmetic_promotes_numeric_types() {
+    assert_eq!(MATH.result("(1 2 3) math:sum ?r"), typed("6", "integer"));
+    assert_eq!(MATH.result("(1 2.5) math:sum ?r"), typed("3.5", "decimal"));
+    assert_eq!(MATH.result("(1 2.5e0) math:sum ?r"), typed("3.5E0", "double"));
+    assert_eq!(MATH.result("(\"1.5\"^^xsd:float 2) math:product ?r"), typed("3.0E0", "float"));
+    assert_eq!(MATH.result("(0.1 0.2) math:sum ?r"), typed("0.3", "decimal"));
+    assert_eq!(MATH.result("(7 10) math:difference ?r"), typed("-3", "integer"));
+    assert_eq!(MATH.result("(\"5\"^^xsd:int 2) math:remainder ?r"), typed("1", "integer"));
+    assert_eq!(MATH.result("(7 2) math:integerQuotient ?r"), typed("3", "integer"));
+
+    // Dividing integers gives a decimal, and dividing by zero fails
+    assert_eq!(MATH.result("(7 2) math:quotient ?r"), typed("3.5", "decimal"));
+    assert_eq!(MATH.result("(6 3) math:quotient ?r"), typed("2.0", "decimal"));
+    assert!(MATH.derive("(1 0) math:quotient ?r", "?r").is_empty());
+}
+
+#[test]
+fn test_integers_have_arbitrary_precision() {
+    assert_eq!(MATH.result("(2 100) math:exponentiation ?r"), typed("1267650600228229401496703205376", "integer"));
+    assert_eq!(
+        MATH.result("(9223372036854775807 1) math:sum ?r"),
+        typed("9223372036854775808", "integer")
+    );
+    assert_eq!(MATH.result("(2 -1) math:exponentiation ?r"), typed("5.0E-1", "double"));
+
+    // Powers too large to compute exactly overflow to a double instead of hanging
+    assert_eq!(MATH.result("(3 4000000000) math:exponentiation ?r"), typed("INF", "double"));
+    assert_eq!(MATH.result("(-1 4000000001) math:exponentiation ?r"), typed("-1", "integer"));
+}
+
+#[test]
+fn test_unary_functions() {
+    assert_eq!(MATH.result("-4.25 math:absoluteValue ?r"), typed("4.25", "decimal"));
+    assert_eq!(MATH.result("3 math:negation ?r"), typed("-3", "integer"));
+    assert_eq!(MATH.result("2.5 math:rounded ?r"), typed("3", "integer"));
+    assert_eq!(MATH.result("-2.5 math:rounded ?r"), typed("-3", "integer"));
+    assert_eq!(MATH.result("-2.5 math:floor ?r"), typed("-3", "integer"));
+    assert_eq!(MATH.result("2.1e0 math:ceiling ?r"), typed("3", "integer"));
+    assert_eq!(MATH.result("0 math:cos ?r"), typed("1.0E0", "double"));
+    assert_eq!(MATH.result("180 math:radians ?r"), typed(&format!("{:E}", std::f64::consts::PI), "double"));
+}
+
+#[test]
+fn test_comparisons() {
+    let document = ":a :value 3 . :b :value 4.5 . :c :value 1e1 .\n\
+                    { ?x :value ?v . ?v math:greaterThan 4 } => { ?x :big true } .\n\
+                    { ?x :value ?v . ?v math:notGreaterThan 3.0 } => { ?x :small true } .\n\
+                    { ?x :value ?v . ?v math:notEqualTo 3 . ?v math:lessThan 10 } => { ?x :middling true } .\n\
+                    { ?x :value ?v . ?v math:equalTo 10 } => { ?x :ten true } .";
+    let mut engine = MATH.engine(document);
+    let derived = engine.saturate().unwrap();
+    let has = |subject: &str, predicate: &str| {
+        let statement = MATH.parse(&format!(":{} :{} true .", subject, predicate));
+        derived.contains(statement.statements.iter().next().unwrap())
+    };
+    assert!(has("b", "big") && has("c", "big") && !has("a", "big"));
+    assert!(has("a", "small") && !has("b", "small"));
+    assert!(has("b", "middling") && !has("a", "middling") && !has("c", "middling"));
+    assert!(has("c", "ten"));
+
+    // A bound result is compared numerically
+    assert!(MATH.holds("(1 2) math:sum 3.0"));
+}
+
+#[test]
+fn test_builtin_evaluations_are_recorded_as_evidence() {
+    let document = ":a :price 10 . :a :quantity 3 .\n\
+                    { ?x :price ?p . ?x :quantity ?q . (?p ?q) math:product ?t } => { ?x :total ?t } .\n\
+                    { ?x :discounted ?d } <= { ?x :total ?t . (?t 5) math:difference ?d } .";
+    let mut engine = MATH.engine(document);
+    engine.saturate().unwrap();
+
+    let proof = engine.get_proof();
+    let step = proof.steps.iter().find(|step| step.rule == "rule1").expect("the forward rule fired");
+    let product: Vec<Statement> = MATH.parse("(10 3) math:product 30 .").statements.iter().cloned().collect();
+    assert_eq!(step.evidence, product);
+
+    engine.set_goal(MATH.parse(":a :discounted ?d ."));
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("the goal should be proven");
+    };
+    let step = proof.steps.iter().find(|step| step.rule == "rule2").expect("the backward rule was used");
+    let difference: Vec<Statement> = MATH.parse("(30 5) math:difference 25 .").statements.iter().cloned().collect();
+    assert_eq!(step.evidence, difference);
+    assert!(proof.is_valid().unwrap());
+}
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use n3proof::{parse_n3, Formula, ParseOptions, Statement, Term};
+use oxrdf::NamedNode;
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+fn parse(input: &str) -> Formula {
+    parse_n3(input.as_bytes(), ParseOptions::default()).expect("document should parse")
+}
+
+fn iri(value: &str) -> Term {
+    Term::Iri(NamedNode::new(value).unwrap())
+}
+
+fn ex(local: &str) -> Term {
+    iri(&format!("http://example.org/{}", local))
+}
+
+#[test]
+fn test_list_terms_display_and_hash() {
+    let list = Term::List(vec![ex("a"), Term::List(vec![]), Term::Variable("x".to_string())]);
+    assert_eq!(list.to_string(), "( <http://example.org/a> ( ) ?x )");
+
+    let mut set = std::collections::HashSet::new();
+    set.insert(list.clone());
+    assert!(set.contains(&Term::List(vec![ex("a"), Term::List(vec![]), Term::Variable("x".to_string())])));
+    assert!(!set.contains(&Term::List(vec![ex("a")])));
+}
+
+#[test]
+fn test_expand_lists_to_rdf() {
+    let formula = parse("@prefix : <http://example.org/> .\n:s :p ( :a ( :b ) ) .\n");
+    let expanded = formula.expand_lists();
+
+    // Two nodes for the outer list, one for the inner list, plus the original statement
+    assert_eq!(expanded.statements.len(), 7);
+    assert!(expanded.statements.iter().all(|s| !matches!(s.object, Term::List(_))));
+    assert_eq!(
+        expanded.statements.iter().filter(|s| s.object == iri(&format!("{}nil", RDF))).count(),
+        2
+    );
+
+    assert_eq!(expanded.collapse_lists(), formula);
+}
+
+#[test]
+fn test_collapse_lists_from_rdf() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .\n\
+         :s :p _:l1 .\n\
+         _:l1 rdf:first :a ; rdf:rest _:l2 .\n\
+         _:l2 rdf:first :b ; rdf:rest rdf:nil .\n\
+         _:bad rdf:first :c ; rdf:rest rdf:nil ; :other :d .\n\
+         { :x :y _:l3 . _:l3 rdf:first :e ; rdf:rest rdf:nil } => { :x :z :w } .\n",
+    );
+
+    let collapsed = formula.collapse_lists();
+    assert_eq!(collapsed.statements[0], Statement {
+        subject: ex("s"),
+        predicate: ex("p"),
+        object: Term::List(vec![ex("a"), ex("b")]),
+    });
+
+    // The node with an extra property is not a well-formed list and stays as RDF
+    assert_eq!(collapsed.statements.len(), 5);
+    assert_eq!(
+        collapsed.statements.iter().filter(|s| s.predicate == ex("other")).count(),
+        1
+    );
+
+    let rule = collapsed.statements.as_slice().last().unwrap();
+    let premise = match &rule.subject {
+        Term::Formula(premise) => Arc::clone(premise),
+        other => panic!("expected a formula, found {}", other),
+    };
+    assert_eq!(premise.statements, vec![Statement {
+        subject: ex("x"),
+        predicate: ex("y"),
+        object: Term::List(vec![ex("e")]),
+    }]);
+}
+
+fn hash_of<T: std::hash::Hash>(value: &T) -> u64 {
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn quoted(document: &str) -> Term {
+    let formula = parse(&format!("@prefix : <http://example.org/> .\n:s :p {{ {} }} .\n", document));
+    formula.statements[0].object.clone()
+}
+
+#[test]
+fn test_formula_terms_hash_by_content() {
+    let a = quoted(":a :b :c . :d :e { :f :g :h } .");
+    let b = quoted(":d :e { :f :g :h } . :a :b :c . :a :b :c .");
+    let c = quoted(":a :b :c .");
+
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+    assert_ne!(a, c);
+
+    let mut set = std::collections::HashSet::new();
+    set.insert(a.clone());
+    set.insert(b);
+    set.insert(c);
+    assert_eq!(set.len(), 2);
+
+    let mut index = std::collections::HashMap::new();
+    index.insert(Statement { subject: a, predicate: ex("p"), object: ex("o") }, 1);
+    let lookup = Statement { subject: quoted(":d :e { :f :g :h } . :a :b :c ."), predicate: ex("p"), object: ex("o") };
+    assert_eq!(index.get(&lookup), Some(&1));
+}
+
+#[test]
+fn test_formula_hash_is_stable_under_renaming() {
+    let x = parse("@prefix : <http://example.org/> . { ?x :p _:b . ?x :q ( ?x ) } => { ?x :r :s } .");
+    let y = parse("@prefix : <http://example.org/> . { ?y :p _:c . ?y :q ( ?y ) } => { ?y :r :s } .");
+
+    assert_ne!(x, y);
+    assert_eq!(hash_of(&x.statements[0].subject), hash_of(&y.statements[0].subject));
+    assert_eq!(hash_of(&x), hash_of(&y));
+}
+
+#[test]
+fn test_isomorphism_up_to_renaming() {
+    let a = parse(
+        "@prefix : <http://example.org/> .\n\
+         _:a :knows _:b . _:b :knows _:a . _:a :name \"A\" .\n\
+         { ?x :p _:c } => { ?x :q [ :r ?x ] } .\n",
+    );
+    let b = parse(
+        "@prefix : <http://example.org/> .\n\
+         { ?y :p _:z } => { ?y :q [ :r ?y ] } .\n\
+         _:n :name \"A\" . _:m :knows _:n . _:n :knows _:m .\n",
+    );
+    let c = parse(
+        "@prefix : <http://example.org/> .\n\
+         _:a :knows _:b . _:b :knows _:a . _:b :name \"A\" .\n\
+         { ?x :p _:c } => { ?x :q [ :r :x ] } .\n",
+    );
+
+    assert_ne!(a, b);
+    assert!(a.is_isomorphic(&b));
+    assert!(n3proof::formulas_equivalent(&a, &b));
+    assert!(!a.is_isomorphic(&c));
+    assert_eq!(a.canonical_form(), b.canonical_form());
+    assert_eq!(a.canonicalize(), b.canonicalize());
+}
+
+#[test]
+fn test_isomorphism_distinguishes_regular_graphs() {
+    // Two triangles and one hexagon look the same to hash refinement alone
+    let triangles = parse(
+        "@prefix : <http://example.org/> .\n\
+         _:a :p _:b . _:b :p _:c . _:c :p _:a .\n\
+         _:d :p _:e . _:e :p _:f . _:f :p _:d .\n",
+    );
+    let hexagon = parse(
+        "@prefix : <http://example.org/> .\n\
+         _:a :p _:b . _:b :p _:c . _:c :p _:d .\n\
+         _:d :p _:e . _:e :p _:f . _:f :p _:a .\n",
+    );
+    let shuffled = parse(
+        "@prefix : <http://example.org/> .\n\
+         _:f :p _:d . _:e :p _:f . _:x :p _:y .\n\
+         _:d :p _:e . _:z :p _:x . _:y :p _:z .\n",
+    );
+
+    assert!(!triangles.is_isomorphic(&hexagon));
+    assert!(triangles.is_isomorphic(&shuffled));
+}
+
+#[test]
+fn test_canonicalize_symmetric_formula() {
+    let mut document = String::from("@prefix : <http://example.org/> .\n");
+    for i in 0..30 {
+        document.push_str(&format!("_:n{} :p :o .\n", i));
+    }
+    let formula = parse(&document);
+
+    let canonical = formula.canonicalize();
+    assert_eq!(canonical.statements.len(), 30);
+    assert!(canonical
+        .statements
+        .iter()
+        .all(|s| matches!(&s.subject, Term::BlankNode(node) if node.as_str().starts_with("c14n"))));
+    assert_eq!(canonical.canonicalize(), canonical);
+    assert!(formula.is_isomorphic(&canonical));
+}
+
+#[test]
+fn test_canonicalize_renames_quantified_variables() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         @forAll :who .\n\
+         { :who :a :Person } => { :who :a :Agent } .\n",
+    );
+    let canonical = formula.canonicalize();
+
+    assert_eq!(canonical.universal_vars.len(), 1);
+    assert!(canonical.universal_vars.contains("c14n0"));
+    assert!(canonical.canonical_form().starts_with("@forAll ?c14n0 .\n"));
+
+    let renamed = parse(
+        "@prefix : <http://example.org/> .\n\
+         @forAll :someone .\n\
+         { :someone :a :Person } => { :someone :a :Agent } .\n",
+    );
+    assert!(formula.is_isomorphic(&renamed));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{derived_results, sorted, Fixture};
+use n3proof::{Error, StratificationPolicy, Term};
+
+const NEGATION: Fixture = Fixture::new("@prefix log: <http://www.w3.org/2000/10/swap/log#> .\n");
+
+#[test]
+fn test_not_includes_waits_for_the_facts_it_searches() {
+    // The negation is written first, but only runs once ages have been derived
+    let document = ":alice a :Person . :bob a :Person . :bob :born 1990 .\n\
+                    { ?p a :Person . ?SCOPE log:notIncludes { ?p :age ?a } } => { :result :is ?p } .\n\
+                    { ?p :born ?year } => { ?p :age 36 } .";
+    assert_eq!(sorted(NEGATION.results(document)), vec![NEGATION.term(":alice")]);
+
+    // A quoted formula as scope is searched instead of the knowledge base
+    let document = ":alice :age 30 .\n\
+                    { { :bob :age 40 } log:notIncludes { :alice :age ?a } } => { :result :is true } .";
+    assert_eq!(sorted(NEGATION.results(document)), vec![NEGATION.term("true")]);
+}
+
+#[test]
+fn test_includes_waits_for_the_facts_it_searches() {
+    // The search is written before the rule deriving what it finds
+    let document = ":a :p 1 .\n\
+                    { ?SCOPE log:includes { :a :q ?y } } => { :result :is ?y } .\n\
+                    { :a :p ?x } => { :a :q ?x } .";
+    assert_eq!(sorted(NEGATION.results(document)), vec![NEGATION.term("1")]);
+}
+
+#[test]
+fn test_includes_sees_facts_added_between_saturations() {
+    let mut engine = NEGATION.engine("{ ?SCOPE log:includes { ?x :p :c } } => { :result :is ?x } .");
+    assert!(derived_results(&mut engine).is_empty());
+
+    engine.add_axiom(NEGATION.parse(":z :p :c ."));
+    assert_eq!(derived_results(&mut engine), vec![NEGATION.term(":z")]);
+}
+
+#[test]
+fn test_collect_all_in() {
+    let document = ":a :value 1 . :b :value 2 . :c :other 3 .\n\
+                    { (?v { ?x :value ?v } ?list) log:collectAllIn ?SCOPE } => { :result :is ?list } .";
+    let values = sorted(NEGATION.results(document));
+    assert_eq!(values.len(), 1);
+    let Term::List(items) = &values[0] else {
+        panic!("expected a list, got {}", values[0]);
+    };
+    let mut items: Vec<String> = items.iter().map(ToString::to_string).collect();
+    items.sort();
+    assert_eq!(items, vec![NEGATION.term("1").to_string(), NEGATION.term("2").to_string()]);
+
+    let document = "{ (?x { ?x :p :o } ?list) log:collectAllIn { :a :p :o . :b :q :o } } => { :result :is ?list } .\n\
+                    { (?x { ?x :p :missing } ()) log:collectAllIn { :a :p :o } } => { :result :is :none } .";
+    assert_eq!(sorted(NEGATION.results(document)), vec![NEGATION.term(":none"), NEGATION.term("(:a)")]);
+}
+
+#[test]
+fn test_collect_all_in_sees_facts_added_between_saturations() {
+    let mut engine = NEGATION.engine(
+        ":a :value 1 .\n\
+         { :a :value ?first . (?v { ?x :value ?v } ?list) log:collectAllIn ?SCOPE } => { :result :is ?list } .",
+    );
+    assert_eq!(derived_results(&mut engine), vec![NEGATION.term("(1)")]);
+
+    // Only the search finds the new fact, and the new list is derived besides the old one
+    engine.add_axiom(NEGATION.parse(":b :value 2 ."));
+    let values = derived_results(&mut engine);
+    assert_eq!(values.len(), 1);
+    let Term::List(items) = &values[0] else {
+        panic!("expected a list, got {}", values[0]);
+    };
+    assert_eq!(sorted(items.clone()), vec![NEGATION.term("1"), NEGATION.term("2")]);
+}
+
+#[test]
+fn test_for_all_in() {
+    let document = ":alice a :Person . :alice :name \"Alice\" . :bob a :Person .\n\
+                    { ({ ?p a :Person } { ?p :name ?n }) log:forAllIn ?SCOPE } => { :result :is :allNamed } .\n\
+                    { ({ ?p :name ?n } { ?p a :Person }) log:forAllIn ?SCOPE } => { :result :is :namedArePeople } .";
+    assert_eq!(sorted(NEGATION.results(document)), vec![NEGATION.term(":namedArePeople")]);
+}
+
+#[test]
+fn test_unstratifiable_rules_are_rejected_or_warned_about() {
+    let document = "{ ?SCOPE log:notIncludes { :a :p :b } } => { :a :q :b } .\n\
+                    { :a :q :b } => { :a :p :b } .";
+    let mut rejecting = NEGATION.engine(document);
+    assert!(matches!(rejecting.saturate(), Err(Error::StratificationError(_))));
+
+    let mut warning = NEGATION.engine(document);
+    warning.set_stratification_policy(StratificationPolicy::Warn);
+    assert!(warning.saturate().is_ok());
+
+    // Negation over a predicate no rule derives, or only positive recursion, is fine
+    let document = ":a :edge :b . :b :edge :c .\n\
+                    { ?x :edge ?y } => { ?x :path ?y } .\n\
+                    { ?x :path ?y . ?y :path ?z } => { ?x :path ?z } .\n\
+                    { ?x :path ?y . ?SCOPE log:notIncludes { ?x :blocked true } } => { :result :is ?y } .";
+    assert_eq!(sorted(NEGATION.results(document)), vec![NEGATION.term(":b"), NEGATION.term(":c")]);
+}
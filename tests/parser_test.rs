@@ -0,0 +1,388 @@
+use n3proof::{parse_n3, Error, Formula, N3Parser, ParseError, ParseOptions, SourceLocation, Statement, Term};
+use oxrdf::{Literal, NamedNode};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const LOG: &str = "http://www.w3.org/2000/10/swap/log#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+fn parse(input: &str) -> Formula {
+    parse_n3(input.as_bytes(), ParseOptions::default()).expect("document should parse")
+}
+
+fn iri(value: &str) -> Term {
+    Term::Iri(NamedNode::new(value).unwrap())
+}
+
+fn ex(local: &str) -> Term {
+    iri(&format!("http://example.org/{}", local))
+}
+
+fn triple(subject: Term, predicate: Term, object: Term) -> Statement {
+    Statement { subject, predicate, object }
+}
+
+fn formula_of(term: &Term) -> &Formula {
+    match term {
+        Term::Formula(formula) => formula,
+        other => panic!("expected a formula, found {}", other),
+    }
+}
+
+#[test]
+fn test_prefixes_and_predicate_object_lists() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>\n\
+         :alice a :Person ; :knows :bob , :carol ; .\n",
+    );
+
+    assert_eq!(formula.statements, vec![
+        triple(ex("alice"), iri(&format!("{}type", RDF)), ex("Person")),
+        triple(ex("alice"), ex("knows"), ex("bob")),
+        triple(ex("alice"), ex("knows"), ex("carol")),
+    ]);
+}
+
+#[test]
+fn test_rules_with_quick_variables() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         { ?x :parent ?y } => { ?y :child ?x } .\n\
+         { ?y :ancestor ?x } <= { ?x :parent ?y } .\n",
+    );
+
+    assert_eq!(formula.statements.len(), 2);
+    assert!(formula.universal_vars.contains("x"));
+    assert!(formula.universal_vars.contains("y"));
+
+    let rule = &formula.statements[0];
+    assert_eq!(rule.predicate, iri(&format!("{}implies", LOG)));
+    let premise = formula_of(&rule.subject);
+    assert_eq!(premise.statements, vec![triple(
+        Term::Variable("x".to_string()),
+        ex("parent"),
+        Term::Variable("y".to_string()),
+    )]);
+
+    let backward = &formula.statements[1];
+    assert_eq!(backward.predicate, iri(&format!("{}isImpliedBy", LOG)));
+    assert_eq!(formula_of(&backward.subject).statements.len(), 1);
+}
+
+#[test]
+fn test_literals() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\
+         :s :p \"chat\"@fr, 'plain', \"\"\"multi\n\"line\" here\"\"\", \"5\"^^xsd:int ,\n\
+            42, -1.5, 1e3, true .\n",
+    );
+
+    let objects: Vec<Term> = formula.statements.iter().map(|s| s.object.clone()).collect();
+    assert_eq!(objects, vec![
+        Term::Literal(Literal::new_language_tagged_literal("chat", "fr").unwrap()),
+        Term::Literal(Literal::new_simple_literal("plain")),
+        Term::Literal(Literal::new_simple_literal("multi\n\"line\" here")),
+        Term::Literal(Literal::new_typed_literal("5", NamedNode::new(format!("{}int", XSD)).unwrap())),
+        Term::Literal(Literal::new_typed_literal("42", NamedNode::new(format!("{}integer", XSD)).unwrap())),
+        Term::Literal(Literal::new_typed_literal("-1.5", NamedNode::new(format!("{}decimal", XSD)).unwrap())),
+        Term::Literal(Literal::new_typed_literal("1e3", NamedNode::new(format!("{}double", XSD)).unwrap())),
+        Term::Literal(Literal::new_typed_literal("true", NamedNode::new(format!("{}boolean", XSD)).unwrap())),
+    ]);
+}
+
+#[test]
+fn test_blank_nodes_and_collections() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         :alice :knows [ :name \"Bob\" ] .\n\
+         _:x :p _:x .\n\
+         :list :items ( :a :b ) .\n\
+         :empty :items () .\n",
+    );
+
+    assert_eq!(formula.statements.len(), 5);
+
+    let knows = &formula.statements[1];
+    assert_eq!(knows.predicate, ex("knows"));
+    assert!(matches!(knows.object, Term::BlankNode(_)));
+    assert_eq!(formula.statements[0].subject, knows.object);
+
+    let reflexive = &formula.statements[2];
+    assert_eq!(reflexive.subject, reflexive.object);
+
+    assert_eq!(formula.statements[3], triple(ex("list"), ex("items"), Term::List(vec![ex("a"), ex("b")])));
+    assert_eq!(formula.statements[4], triple(ex("empty"), ex("items"), Term::List(Vec::new())));
+}
+
+#[test]
+fn test_paths_and_inverse_verbs() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         :joe!:mother :name \"Ann\" .\n\
+         :bob is :parent of :carol .\n\
+         :dan has :age 40 .\n",
+    );
+
+    let mother = &formula.statements[0];
+    assert_eq!(mother.subject, ex("joe"));
+    assert_eq!(mother.predicate, ex("mother"));
+    assert_eq!(formula.statements[1].subject, mother.object);
+
+    assert_eq!(formula.statements[2], triple(ex("carol"), ex("parent"), ex("bob")));
+    assert_eq!(formula.statements[3].predicate, ex("age"));
+}
+
+#[test]
+fn test_explicit_quantification() {
+    let formula = parse(
+        "@prefix : <http://example.org/> .\n\
+         @forAll :x .\n\
+         { :x a :Man } => { :x a :Mortal } .\n",
+    );
+
+    let variable = Term::Variable("http://example.org/x".to_string());
+    assert!(formula.universal_vars.contains("http://example.org/x"));
+    let premise = formula_of(&formula.statements[0].subject);
+    assert_eq!(premise.statements[0].subject, variable);
+}
+
+#[test]
+fn test_syntax_errors() {
+    let undefined_prefix = parse_n3("ex:a ex:b ex:c .".as_bytes(), ParseOptions::default());
+    assert!(undefined_prefix.is_err());
+
+    let missing_dot = parse_n3(
+        "@prefix : <http://example.org/> . :a :b :c".as_bytes(),
+        ParseOptions::default(),
+    );
+    let message = missing_dot.unwrap_err().to_string();
+    assert!(message.contains("line 1"), "unexpected message: {}", message);
+
+    let unterminated = parse_n3("{ <http://a> <http://b> <http://c> .".as_bytes(), ParseOptions::default());
+    assert!(unterminated.is_err());
+}
+
+#[test]
+fn test_parse_mock_proof() {
+    let content = include_str!("mock_proof.n3");
+    let formula = parse(content);
+
+    assert_eq!(formula.statements.len(), 5);
+    assert!(formula.statements.contains(&triple(
+        iri("http://example.org/socrates#Socrates"),
+        iri(&format!("{}type", RDF)),
+        iri("http://example.org/socrates#Mortal"),
+    )));
+}
+
+#[test]
+fn test_base_iri_from_options() {
+    let options = ParseOptions {
+        base_iri: Some("http://example.org/dir/doc".to_string()),
+        ..ParseOptions::default()
+    };
+    let formula = parse_n3(
+        "<#a> <p> <../b> .\n\
+         @prefix rel: <ns/> .\n\
+         rel:c <//other.org/q> <?x=1> .\n"
+            .as_bytes(),
+        options,
+    ).unwrap();
+
+    assert_eq!(formula.statements, vec![
+        triple(
+            iri("http://example.org/dir/doc#a"),
+            iri("http://example.org/dir/p"),
+            iri("http://example.org/b"),
+        ),
+        triple(
+            iri("http://example.org/dir/ns/c"),
+            iri("http://other.org/q"),
+            iri("http://example.org/dir/doc?x=1"),
+        ),
+    ]);
+}
+
+#[test]
+fn test_base_directives_change_base() {
+    let formula = parse(
+        "@base <http://a/b/c/d;p?q> .\n\
+         <g> <g;x> <../../g> .\n\
+         @base <../other/> .\n\
+         <s> <p> <o> .\n\
+         BASE <http://example.com/>\n\
+         <s> <p> <o> .\n",
+    );
+
+    assert_eq!(formula.statements, vec![
+        triple(iri("http://a/b/c/g"), iri("http://a/b/c/g;x"), iri("http://a/g")),
+        triple(iri("http://a/b/other/s"), iri("http://a/b/other/p"), iri("http://a/b/other/o")),
+        triple(iri("http://example.com/s"), iri("http://example.com/p"), iri("http://example.com/o")),
+    ]);
+}
+
+#[test]
+fn test_relative_iri_without_base() {
+    let result = parse_n3("<s> <http://example.org/p> <http://example.org/o> .".as_bytes(), ParseOptions::default());
+    let message = result.unwrap_err().to_string();
+    assert!(message.contains("without a base IRI"), "unexpected message: {}", message);
+}
+
+#[test]
+fn test_turtle_mode_rejects_n3_extensions() {
+    let turtle = ParseOptions {
+        allow_n3_extensions: false,
+        ..ParseOptions::default()
+    };
+    let prologue = "@prefix : <http://example.org/> .\n";
+
+    let data = format!("{}:a :b ( 1 2 ) ; :c [ :d \"x\"@en ] .\n[ :e :f ] .\n", prologue);
+    let formula = parse_n3(data.as_bytes(), turtle.clone()).expect("plain Turtle should parse");
+    assert_eq!(formula.statements.len(), 4);
+
+    let rejected = [
+        "{ :a :b :c } => { :a :b :d } .",
+        ":a :b { :c :d :e } .",
+        "?x :b :c .",
+        "@forAll :x . :x :b :c .",
+        ":a!:b :c :d .",
+        ":a = :b .",
+        ":a is :b of :c .",
+        "\"lit\" :b :c .",
+    ];
+    for body in rejected {
+        let input = format!("{}{}", prologue, body);
+        let error = parse_n3(input.as_bytes(), turtle.clone())
+            .expect_err(&format!("'{}' should be rejected in Turtle mode", body));
+        assert!(error.to_string().contains("line 2"), "unexpected message: {}", error);
+        assert!(parse_n3(input.as_bytes(), ParseOptions::default()).is_ok());
+    }
+}
+
+#[test]
+fn test_parse_error_location_and_snippet() {
+    let source = "@prefix : <http://example.org/> .\n:a :b :c .\n:d :e ex:f .\n";
+    let options = ParseOptions {
+        file_name: Some("rules.n3".to_string()),
+        ..ParseOptions::default()
+    };
+
+    let error = match parse_n3(source.as_bytes(), options) {
+        Err(Error::ParseError(error)) => error,
+        other => panic!("expected a parse error, got {:?}", other),
+    };
+
+    assert_eq!(error.location, Some(SourceLocation {
+        offset: 51,
+        line: 3,
+        column: 7,
+        file: Some("rules.n3".to_string()),
+    }));
+    assert_eq!(error.token.as_deref(), Some("ex:f"));
+    assert_eq!(
+        error.render(source),
+        "error: Undefined prefix 'ex:'\n \
+         --> rules.n3:3:7\n  \
+         |\n\
+         3 | :d :e ex:f .\n  \
+         |       ^^^^\n"
+    );
+}
+
+#[test]
+fn test_render_points_at_the_line_start_for_column_zero() {
+    let error = ParseError::new("Unexpected token").at(SourceLocation { offset: 0, line: 1, column: 0, file: None });
+    let rendered = error.render(":a :b :c .\n");
+    assert!(rendered.ends_with("1 | :a :b :c .\n  | ^\n"), "rendered {:?}", rendered);
+}
+
+#[test]
+fn test_recovering_parse_collects_diagnostics() {
+    let source = "@prefix : <http://example.org/> .\n\
+                  :a :b :c .\n\
+                  :d :e $ .\n\
+                  { :f :g :h . :i :j . :k :l :m } => { :n :o :p } .\n\
+                  :q :r ex:s .\n\
+                  :t :u :v .\n";
+    let parser = N3Parser::new(ParseOptions::default()).unwrap();
+
+    assert!(parser.parse(source.as_bytes()).is_err());
+
+    let (formula, diagnostics) = parser.parse_with_recovery(source.as_bytes()).unwrap();
+    let lines: Vec<usize> = diagnostics.iter()
+        .map(|d| d.location.as_ref().unwrap().line)
+        .collect();
+    assert_eq!(lines, vec![3, 4, 5], "diagnostics: {:?}", diagnostics);
+
+    assert_eq!(formula.statements.len(), 3);
+    assert_eq!(formula.statements[0], triple(ex("a"), ex("b"), ex("c")));
+    assert_eq!(formula_of(&formula.statements[1].subject).statements.len(), 2);
+    assert_eq!(formula.statements[2], triple(ex("t"), ex("u"), ex("v")));
+}
+
+/// Reader that records how many bytes have been pulled from it
+struct CountingReader<'a> {
+    data: &'a [u8],
+    consumed: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl std::io::Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.data.len() - self.consumed.get());
+        let start = self.consumed.get();
+        buf[..n].copy_from_slice(&self.data[start..start + n]);
+        self.consumed.set(start + n);
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_streaming_statements() {
+    let mut source = String::from("@prefix : <http://example.org/> .\n{ ?x a :A } => { ?x a :B } .\n");
+    for i in 0..20_000 {
+        source.push_str(&format!(":item{} a :A ; :index {} .\n", i, i));
+    }
+
+    let consumed = std::rc::Rc::new(std::cell::Cell::new(0));
+    let reader = CountingReader { data: source.as_bytes(), consumed: consumed.clone() };
+    let parser = N3Parser::new(ParseOptions::default()).unwrap();
+    let mut statements = parser.statements(reader).unwrap();
+
+    let rule = statements.next().unwrap().unwrap();
+    assert_eq!(rule.predicate, iri(&format!("{}implies", LOG)));
+    assert!(statements.universal_vars().contains("x"));
+    assert!(consumed.get() < source.len() / 10, "read {} bytes for the first statement", consumed.get());
+
+    let rest: Vec<Statement> = statements.map(|s| s.unwrap()).collect();
+    assert_eq!(rest.len(), 40_000);
+    assert_eq!(rest[1], triple(
+        ex("item0"),
+        ex("index"),
+        Term::Literal(Literal::new_typed_literal("0", NamedNode::new(format!("{}integer", XSD)).unwrap())),
+    ));
+    assert_eq!(consumed.get(), source.len());
+}
+
+#[test]
+fn test_streaming_blank_node_labels() {
+    let parser = N3Parser::new(ParseOptions::default()).unwrap();
+    let source = "_:a <http://p> _:b0 .\n<http://s> <http://p> [] .\n<http://s> <http://q> _:a .\n";
+    let statements: Vec<Statement> = parser.statements(source.as_bytes()).unwrap().map(|s| s.unwrap()).collect();
+
+    // A label names the same blank node in every statement, and never one written as []
+    assert_eq!(statements[0].subject, statements[2].object);
+    assert_ne!(statements[0].object, statements[1].object);
+    assert_ne!(statements[0].subject, statements[0].object);
+}
+
+#[test]
+fn test_streaming_stops_at_first_error() {
+    let parser = N3Parser::new(ParseOptions::default()).unwrap();
+    let source = "<http://a> <http://b> <http://c> .\n<http://d> <http://e> .\n<http://f> <http://g> <http://h> .\n";
+    let results: Vec<_> = parser.statements(source.as_bytes()).unwrap().collect();
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
@@ -0,0 +1,88 @@
+use n3proof::{create_proof_engine, parse_n3, Formula, ParseOptions, Program, RuleDirection, Statement, Term};
+use oxrdf::NamedNode;
+
+fn parse(input: &str) -> Formula {
+    let document = format!("@prefix : <http://example.org/> .\n@prefix log: <http://www.w3.org/2000/10/swap/log#> .\n{}", input);
+    parse_n3(document.as_bytes(), ParseOptions::default()).expect("document should parse")
+}
+
+fn ex(local: &str) -> Term {
+    Term::Iri(NamedNode::new(format!("http://example.org/{}", local)).unwrap())
+}
+
+#[test]
+fn test_splits_facts_rules_and_queries() {
+    let program = Program::from_formula(&parse(
+        r#"
+        :socrates a :Human .
+        { ?x a :Human } => { ?x a :Mortal } .
+        { ?x :dies true } <= { ?x a :Mortal } .
+        { ?x a :Mortal } log:query { ?x a :Answer } .
+        true => { :world :exists true } .
+        :note :about { { :a :b :c } => { :d :e :f } } .
+        "#,
+    ));
+
+    assert_eq!(program.facts.statements.len(), 2);
+    assert!(program.facts.universal_vars.is_empty());
+    let names: Vec<&str> = program.rules().map(|rule| rule.name.as_str()).collect();
+    assert_eq!(names, ["rule1", "rule3", "rule2"]);
+    assert_eq!(program.backward_rules[0].direction, RuleDirection::Backward);
+    assert_eq!(program.backward_rules[0].premises[0].statements, parse("?x a :Mortal .").statements);
+    assert_eq!(program.backward_rules[0].conclusion.statements, parse("?x :dies true .").statements);
+    assert!(program.forward_rules[1].premises[0].statements.is_empty());
+    assert_eq!(program.queries.len(), 1);
+    assert_eq!(program.queries[0].name, "query1");
+}
+
+#[test]
+fn test_variable_scopes() {
+    let program = Program::from_formula(&parse(
+        r#"
+        @forAll :x .
+        @forSome :someone .
+        :someone a :Human .
+        { :x a :Human } => { :x a :Mortal } .
+        { @forAll :y . :y :likes :x } => { :x :likedBy :everyone } .
+        { @forAll :x . :x :likes :x } => { :x :likesItself true } .
+        "#,
+    ));
+
+    // A top-level @forSome variable is one blank node shared by facts and rules
+    let fact = program.facts.statements.iter().next().unwrap();
+    assert!(matches!(fact.subject, Term::BlankNode(_)));
+
+    // @forAll at the top level makes :x a rule variable on both sides
+    let x = Term::Variable("http://example.org/x".to_string());
+    let mortal = &program.forward_rules[0];
+    assert!(mortal.premises[0].statements.iter().all(|statement| statement.subject == x));
+    assert!(mortal.conclusion.statements.iter().all(|statement| statement.subject == x));
+
+    // @forAll inside a premise stays local to it, even when a rule variable has the same name
+    let liked = &program.forward_rules[1];
+    assert_eq!(liked.premises[0].universal_vars.len(), 1);
+    assert!(!liked.premises[0].universal_vars.contains("http://example.org/y"));
+    assert!(liked.conclusion.universal_vars.is_empty());
+
+    let shadowing = &program.forward_rules[2];
+    let premise = shadowing.premises[0].statements.iter().next().unwrap();
+    assert_ne!(premise.subject, x);
+    assert!(shadowing.premises[0].universal_vars.iter().any(|var| premise.subject == Term::Variable(var.clone())));
+    assert!(shadowing.conclusion.statements.iter().all(|statement| statement.subject == x));
+}
+
+#[test]
+fn test_loads_program_into_engine() {
+    let program = Program::from_formula(&parse(
+        r#"
+        :socrates a :Human .
+        { ?x a :Human } => { ?x a :Mortal } .
+        "#,
+    ));
+    let mut engine = create_proof_engine();
+    engine.add_program(&program);
+    engine.saturate().unwrap();
+
+    let rdf_type = Term::Iri(NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap());
+    assert!(engine.contains_fact(&Statement { subject: ex("socrates"), predicate: rdf_type, object: ex("Mortal") }));
+}
@@ -0,0 +1,351 @@
+use n3proof::{
+    create_proof_engine, parse_n3, Formula, ParseOptions, ProofLimits, ProofOutcome, Rule, RuleDirection, Statement, Term,
+};
+use oxrdf::NamedNode;
+
+fn parse(input: &str) -> Formula {
+    let document = format!("@prefix : <http://example.org/> .\n{}", input);
+    parse_n3(document.as_bytes(), ParseOptions::default()).expect("document should parse")
+}
+
+fn ex(local: &str) -> Term {
+    Term::Iri(NamedNode::new(format!("http://example.org/{}", local)).unwrap())
+}
+
+fn triple(subject: Term, predicate: Term, object: Term) -> Statement {
+    Statement { subject, predicate, object }
+}
+
+fn grandparent_rule() -> Rule {
+    Rule::new(
+        "grandparent",
+        vec![parse("?x :parent ?y . ?y :parent ?z .")],
+        parse("?x :grandparent ?z ."),
+    )
+}
+
+#[test]
+fn test_apply_returns_every_match() {
+    let family = parse(":a :parent :b . :b :parent :c . :b :parent :d . :c :parent :e .");
+    let rule = grandparent_rule();
+
+    assert!(rule.can_apply(std::slice::from_ref(&family)));
+    assert_eq!(rule.matches(std::slice::from_ref(&family)).len(), 3);
+
+    let conclusions = rule.apply(&[family]).unwrap();
+    let derived: Vec<&Statement> = conclusions.iter().flat_map(|c| c.statements.iter()).collect();
+    assert_eq!(conclusions.len(), 3);
+    assert!(derived.contains(&&triple(ex("a"), ex("grandparent"), ex("c"))));
+    assert!(derived.contains(&&triple(ex("a"), ex("grandparent"), ex("d"))));
+    assert!(derived.contains(&&triple(ex("b"), ex("grandparent"), ex("e"))));
+    assert!(conclusions.iter().all(|c| c.universal_vars.is_empty()));
+}
+
+#[test]
+fn test_premises_match_across_formulas() {
+    let rule = grandparent_rule();
+    let first = parse(":a :parent :b .");
+    let second = parse(":b :parent :c .");
+
+    assert!(!rule.can_apply(std::slice::from_ref(&first)));
+    let conclusions = rule.apply(&[first, second]).unwrap();
+    assert_eq!(conclusions, vec![parse(":a :grandparent :c .")]);
+}
+
+#[test]
+fn test_apply_without_match_is_an_error() {
+    let error = grandparent_rule().apply(&[parse(":a :parent :b .")]).unwrap_err();
+    assert!(error.to_string().contains("grandparent"), "{}", error);
+}
+
+#[test]
+fn test_distinct_results_and_blank_nodes() {
+    // Two ways to match the premise give the same conclusion, which is returned once
+    let rule = Rule::new(
+        "has_child",
+        vec![parse("?x :parent [] .")],
+        parse("?x :child [ :of ?x ] ."),
+    );
+    let conclusions = rule.apply(&[parse(":a :parent :b . :a :parent :c .")]).unwrap();
+    assert_eq!(conclusions.len(), 1);
+
+    // The conclusion's blank node is fresh rather than the rule's own label
+    let rule_blank = &rule.conclusion.statements[0].object;
+    let derived = conclusions[0].statements.iter().find(|s| s.predicate == ex("child")).unwrap();
+    assert!(matches!(derived.object, Term::BlankNode(_)));
+    assert_ne!(&derived.object, rule_blank);
+}
+
+#[test]
+fn test_engine_apply_rule_records_each_conclusion() {
+    let mut engine = create_proof_engine();
+    let facts = engine.add_axiom(parse(":a :parent :b . :b :parent :c . :b :parent :d ."));
+    let rule = engine.add_rule(grandparent_rule());
+
+    let derived = engine.apply_rule(rule, &[facts]).unwrap();
+    assert_eq!(derived, vec![1, 2]);
+    assert!(engine.contains_fact(&triple(ex("a"), ex("grandparent"), ex("d"))));
+
+    let proof = engine.get_proof();
+    assert_eq!(proof.steps.len(), 3);
+    assert_eq!(proof.steps[2].premises, vec![facts]);
+    assert!(proof.is_valid().unwrap());
+
+    assert!(engine.apply_rule(rule, &[7]).is_err());
+}
+
+#[test]
+fn test_engine_apply_rule_evaluates_builtins() {
+    let mut engine = create_proof_engine();
+    let facts = engine.add_axiom(parse(":a :age 20 . :b :age 3 ."));
+    let rule = engine.add_rule(Rule::new(
+        "adult",
+        vec![parse("@prefix math: <http://www.w3.org/2000/10/swap/math#> .\n?x :age ?n . ?n math:greaterThan 5 .")],
+        parse("?x a :Adult ."),
+    ));
+
+    let derived = engine.apply_rule(rule, &[facts]).unwrap();
+    assert_eq!(derived, vec![1]);
+    let adult = triple(ex("a"), Term::Iri(NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap()), ex("Adult"));
+    assert!(engine.contains_fact(&adult));
+
+    let proof = engine.get_proof();
+    let evidence = parse("@prefix math: <http://www.w3.org/2000/10/swap/math#> .\n20 math:greaterThan 5 .");
+    assert_eq!(proof.steps[1].evidence, evidence.statements.iter().cloned().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_saturate_computes_closure() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":a :ancestor :b . :b :ancestor :c . :c :ancestor :d ."));
+    engine.add_rule(Rule::new(
+        "transitivity",
+        vec![parse("?x :ancestor ?y . ?y :ancestor ?z .")],
+        parse("?x :ancestor ?z ."),
+    ));
+
+    let derived = engine.saturate().unwrap();
+    assert_eq!(derived.len(), 3);
+    assert!(derived.contains(&triple(ex("a"), ex("ancestor"), ex("d"))));
+    assert_eq!(engine.fact_count(), 6);
+
+    // Every derivation is a step whose premises come before it
+    let proof = engine.get_proof();
+    assert_eq!(proof.steps.len(), 4);
+    assert!(proof.is_valid().unwrap());
+    let a_d = proof
+        .steps
+        .iter()
+        .position(|step| step.conclusion.statements.contains(&triple(ex("a"), ex("ancestor"), ex("d"))))
+        .unwrap();
+    assert_eq!(proof.steps[a_d].rule, "transitivity");
+    assert!(proof.steps[a_d].premises.iter().all(|&premise| premise < a_d));
+
+    // Already at the fixpoint
+    assert!(engine.saturate().unwrap().is_empty());
+}
+
+#[test]
+fn test_saturate_chains_rules() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":socrates a :Human . :Human :subClassOf :Mortal . :Mortal :subClassOf :Being ."));
+    engine.add_rule(Rule::new(
+        "subclass",
+        vec![parse("?s a ?a . ?a :subClassOf ?b .")],
+        parse("?s a ?b ."),
+    ));
+    engine.add_rule(Rule::new(
+        "being",
+        vec![parse("?s a :Being .")],
+        parse("?s :exists true ."),
+    ));
+
+    let derived = engine.saturate().unwrap();
+    let rdf_type = Term::Iri(NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap());
+    assert!(derived.contains(&triple(ex("socrates"), rdf_type, ex("Being"))));
+    assert_eq!(derived.matching(None, Some(&ex("exists")), None).count(), 1);
+}
+
+#[test]
+fn test_saturate_fires_existential_rules_once() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":alice a :Person . :bob a :Person ."));
+    engine.add_rule(Rule::new(
+        "everyone_has_a_mother",
+        vec![parse("?p a :Person .")],
+        parse("?p :mother [ a :Mother ] ."),
+    ));
+
+    // One fresh mother per person, and no new ones on a second pass
+    let derived = engine.saturate().unwrap();
+    assert_eq!(derived.matching(None, Some(&ex("mother")), None).count(), 2);
+    assert_eq!(derived.len(), 4);
+    assert!(engine.saturate().unwrap().is_empty());
+}
+
+#[test]
+fn test_saturate_finds_each_binding_once() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":a0 :ancestor :a1 . :a1 :ancestor :a2 . :a2 :ancestor :a3 . :a3 :ancestor :a4 . :a4 :ancestor :a5 ."));
+    engine.add_rule(Rule::new(
+        "transitivity",
+        vec![parse("?x :ancestor ?y . ?y :ancestor ?z .")],
+        parse("?x :ancestor ?z ."),
+    ));
+
+    // The closure of a chain of six nodes has one binding per ordered triple of nodes
+    let derived = engine.saturate().unwrap();
+    assert_eq!(derived.len(), 10);
+    assert_eq!(engine.stats().rule_firings, 20);
+    assert_eq!(engine.stats().derived_statements, 10);
+}
+
+#[test]
+fn test_saturate_resumes_from_new_facts_and_rules() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":a :ancestor :b . :b :ancestor :c ."));
+    engine.add_rule(Rule::new(
+        "transitivity",
+        vec![parse("?x :ancestor ?y . ?y :ancestor ?z .")],
+        parse("?x :ancestor ?z ."),
+    ));
+    engine.saturate().unwrap();
+    assert_eq!(engine.stats().rule_firings, 1);
+
+    // Only bindings that use the new fact are found
+    engine.add_axiom(parse(":c :ancestor :d ."));
+    let derived = engine.saturate().unwrap();
+    assert_eq!(derived.len(), 2);
+    assert_eq!(engine.stats().rule_firings, 4);
+
+    // A new rule still sees every existing fact
+    engine.add_rule(Rule::new(
+        "descendant",
+        vec![parse("?x :ancestor ?y .")],
+        parse("?y :descendant ?x ."),
+    ));
+    let derived = engine.saturate().unwrap();
+    assert_eq!(derived.len(), 6);
+    assert_eq!(engine.stats().rule_firings, 10);
+}
+
+fn mortality_engine() -> n3proof::ProofEngine {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":socrates a :Human ."));
+    let rules = parse("{ ?x a :Mortal } <= { ?x a :Human } .");
+    let rule = Rule::from_statement("mortality", &rules.statements[0]).unwrap();
+    assert_eq!(rule.direction, RuleDirection::Backward);
+    engine.add_rule(rule);
+    engine
+}
+
+#[test]
+fn test_prove_uses_backward_rules() {
+    let mut engine = mortality_engine();
+
+    // Backward rules are not fired by saturation
+    assert!(engine.saturate().unwrap().is_empty());
+
+    engine.set_goal(parse(":socrates a :Mortal ."));
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("goal should be provable");
+    };
+    assert!(proof.is_valid().unwrap());
+    let steps: Vec<&str> = proof.steps.iter().map(|step| step.rule.as_str()).collect();
+    assert_eq!(steps, ["axiom", "mortality", "goal"]);
+    assert_eq!(proof.steps[1].premises, [0]);
+    assert!(engine.goal_proven().unwrap());
+}
+
+#[test]
+fn test_prove_binds_goal_variables() {
+    let mut engine = mortality_engine();
+    engine.set_goal(parse("?who a :Mortal ."));
+
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("goal should be provable");
+    };
+    let rdf_type = Term::Iri(NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap());
+    let last = proof.steps.last().unwrap();
+    assert!(last.conclusion.statements.contains(&triple(ex("socrates"), rdf_type.clone(), ex("Mortal"))));
+    assert!(proof.is_valid().unwrap());
+
+    // Blank nodes in the goal are bound like variables in the final step
+    engine.set_goal(parse("[] a :Mortal ."));
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("goal should be provable");
+    };
+    let last = proof.steps.last().unwrap();
+    assert!(last.conclusion.statements.contains(&triple(ex("socrates"), rdf_type, ex("Mortal"))));
+}
+
+#[test]
+fn test_prove_reports_unprovable_goals() {
+    let mut engine = mortality_engine();
+    assert!(engine.prove().is_err());
+
+    engine.set_goal(parse(":plato a :Mortal ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::NotProvable));
+}
+
+#[test]
+fn test_prove_respects_depth_limit() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":x :p1 true ."));
+    for i in 1..=3 {
+        let rule = parse(&format!("{{ ?s :p{} true }} => {{ ?s :p{} true }} .", i, i + 1));
+        engine.add_rule(Rule::from_statement(&format!("step{}", i), &rule.statements[0]).unwrap());
+    }
+    engine.set_goal(parse(":x :p4 true ."));
+
+    engine.set_limits(ProofLimits { max_depth: 2, ..ProofLimits::default() });
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::LimitReached));
+
+    engine.set_limits(ProofLimits { max_steps: 3, ..ProofLimits::default() });
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::LimitReached));
+
+    engine.set_limits(ProofLimits::default());
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("goal should be provable");
+    };
+    assert_eq!(proof.steps.len(), 5);
+    assert!(proof.is_valid().unwrap());
+}
+
+#[test]
+fn test_prove_terminates_on_left_recursion() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":a :subClassOf :b . :b :subClassOf :c . :c :subClassOf :d . :d :subClassOf :b ."));
+    let rules = parse("{ ?x :subClassOf ?z } <= { ?x :subClassOf ?y . ?y :subClassOf ?z } .");
+    engine.add_rule(Rule::from_statement("transitivity", &rules.statements[0]).unwrap());
+
+    engine.set_goal(parse(":a :subClassOf :d ."));
+    let ProofOutcome::Proven(proof) = engine.prove().unwrap() else {
+        panic!("goal should be provable");
+    };
+    assert!(proof.is_valid().unwrap());
+    for (index, step) in proof.steps.iter().enumerate() {
+        assert!(step.premises.iter().all(|&premise| premise < index));
+    }
+
+    // The cycle between :b, :c and :d never leads back to :a
+    engine.set_goal(parse(":b :subClassOf :a ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::NotProvable));
+}
+
+#[test]
+fn test_prove_reuses_existentials_of_backward_rules() {
+    let mut engine = create_proof_engine();
+    engine.add_axiom(parse(":alice a :Person ."));
+    let rules = parse("{ ?p :mother [] } <= { ?p a :Person } . { ?p :hasMother true } <= { ?p :mother ?m } .");
+    for (i, statement) in rules.statements.iter().enumerate() {
+        engine.add_rule(Rule::from_statement(&format!("rule{}", i), statement).unwrap());
+    }
+
+    engine.set_goal(parse(":alice :hasMother true ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::Proven(_)));
+
+    // The rule only says some mother exists, not who she is
+    engine.set_goal(parse(":alice :mother :eve ."));
+    assert!(matches!(engine.prove().unwrap(), ProofOutcome::NotProvable));
+}
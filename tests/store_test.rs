@@ -0,0 +1,84 @@
+use n3proof::{Statement, Term, TripleStore};
+use oxrdf::NamedNode;
+
+fn ex(local: &str) -> Term {
+    Term::Iri(NamedNode::new(format!("http://example.org/{}", local)).unwrap())
+}
+
+fn triple(subject: &str, predicate: &str, object: &str) -> Statement {
+    Statement { subject: ex(subject), predicate: ex(predicate), object: ex(object) }
+}
+
+fn sample() -> TripleStore {
+    vec![
+        triple("alice", "knows", "bob"),
+        triple("alice", "knows", "carol"),
+        triple("alice", "likes", "bob"),
+        triple("bob", "knows", "carol"),
+        triple("alice", "knows", "bob"),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn count(store: &TripleStore, s: Option<&str>, p: Option<&str>, o: Option<&str>) -> usize {
+    let (s, p, o) = (s.map(ex), p.map(ex), o.map(ex));
+    store.matching(s.as_ref(), p.as_ref(), o.as_ref()).count()
+}
+
+#[test]
+fn test_set_semantics() {
+    let mut store = sample();
+    assert_eq!(store.len(), 4);
+    assert!(!store.insert(triple("bob", "knows", "carol")));
+    assert!(store.contains(&triple("alice", "likes", "bob")));
+    assert_eq!(store[0], triple("alice", "knows", "bob"));
+
+    let reordered: TripleStore = sample().into_iter().rev().collect();
+    assert_eq!(store, reordered);
+}
+
+#[test]
+fn test_pattern_lookup() {
+    let store = sample();
+    assert_eq!(count(&store, None, None, None), 4);
+    assert_eq!(count(&store, Some("alice"), None, None), 3);
+    assert_eq!(count(&store, None, Some("knows"), None), 3);
+    assert_eq!(count(&store, None, None, Some("carol")), 2);
+    assert_eq!(count(&store, Some("alice"), Some("knows"), None), 2);
+    assert_eq!(count(&store, None, Some("knows"), Some("carol")), 2);
+    assert_eq!(count(&store, Some("alice"), None, Some("bob")), 2);
+    assert_eq!(count(&store, Some("alice"), Some("likes"), Some("bob")), 1);
+    assert_eq!(count(&store, Some("carol"), None, None), 0);
+    assert_eq!(count(&store, Some("bob"), Some("likes"), Some("alice")), 0);
+}
+
+#[test]
+fn test_remove_keeps_indexes_consistent() {
+    let mut store = sample();
+    assert!(store.remove(&triple("alice", "knows", "bob")));
+    assert!(!store.remove(&triple("alice", "knows", "bob")));
+
+    assert_eq!(store.len(), 3);
+    assert_eq!(count(&store, Some("alice"), Some("knows"), None), 1);
+    assert_eq!(count(&store, None, None, Some("bob")), 1);
+    // The statement moved into the freed slot is still found through every index
+    assert_eq!(count(&store, Some("bob"), None, None), 1);
+    assert_eq!(count(&store, None, Some("knows"), Some("carol")), 2);
+    assert_eq!(count(&store, Some("bob"), None, Some("carol")), 1);
+
+    store.clear();
+    assert!(store.is_empty());
+    assert_eq!(count(&store, Some("bob"), None, None), 0);
+}
+
+#[test]
+fn test_lookup_scales_with_matches() {
+    let mut store = TripleStore::new();
+    for i in 0..50_000 {
+        store.insert(triple(&format!("s{}", i % 1000), "p", &format!("o{}", i)));
+    }
+    assert_eq!(store.len(), 50_000);
+    assert_eq!(count(&store, Some("s7"), Some("p"), None), 50);
+    assert_eq!(count(&store, None, None, Some("o49999")), 1);
+}
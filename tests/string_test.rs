@@ -0,0 +1,72 @@
+mod common;
+
+use common::{plain, typed, Fixture};
+use n3proof::{BuiltinError, Error, Term};
+use oxrdf::Literal;
+
+const STRING: Fixture = Fixture::new("@prefix string: <http://www.w3.org/2000/10/swap/string#> .\n");
+
+fn tagged(value: &str, language: &str) -> Term {
+    Term::Literal(Literal::new_language_tagged_literal(value, language).unwrap())
+}
+
+#[test]
+fn test_string_functions() {
+    assert_eq!(STRING.values("(\"foo\" \"bar\" 42) string:concatenation ?r"), vec![plain("foobar42")]);
+    assert_eq!(STRING.values("\"héllo\" string:length ?r"), vec![typed("5", "integer")]);
+    assert_eq!(STRING.values("\"MiXeD\" string:lowerCase ?r"), vec![plain("mixed")]);
+    assert_eq!(STRING.values("(\"%s is %s%%\" \"tax\" 20) string:format ?r"), vec![plain("tax is 20%")]);
+}
+
+#[test]
+fn test_language_tags_are_respected() {
+    assert_eq!(STRING.values("\"chat\"@fr string:upperCase ?r"), vec![tagged("CHAT", "fr")]);
+    assert_eq!(STRING.values("(\"a\"@en \"b\"@en) string:concatenation ?r"), vec![tagged("ab", "en")]);
+    assert_eq!(STRING.values("(\"a\"@en \"b\") string:concatenation ?r"), vec![plain("ab")]);
+
+    // A language-tagged argument is only found in a string of the same language
+    assert!(STRING.holds("\"chat noir\"@fr string:contains \"noir\""));
+    assert!(STRING.holds("\"chat noir\"@fr string:startsWith \"chat\"@fr"));
+    assert!(!STRING.holds("\"chat noir\"@fr string:endsWith \"noir\"@en"));
+    assert!(!STRING.holds("\"chat noir\" string:contains \"noir\"@fr"));
+    assert!(STRING.holds("\"abc\"^^xsd:string string:endsWith \"bc\""));
+}
+
+#[test]
+fn test_comparisons() {
+    assert!(STRING.holds("\"apple\" string:lessThan \"banana\""));
+    assert!(!STRING.holds("\"apple\" string:greaterThan \"banana\""));
+    assert!(STRING.holds("\"apple\" string:notGreaterThan \"apple\""));
+    assert!(STRING.holds("\"Apple\" string:equalIgnoringCase \"aPPLE\""));
+    assert!(STRING.holds("\"Apple\" string:containsIgnoringCase \"PP\""));
+}
+
+#[test]
+fn test_regular_expressions() {
+    assert!(STRING.holds("\"2024-05-01\" string:matches \"^\\\\d{4}-\\\\d{2}-\\\\d{2}$\""));
+    assert!(STRING.holds("\"2024-05-01\" string:notMatches \"^[a-z]+$\""));
+    // As in PCRE, \\d only matches ASCII digits
+    assert!(STRING.holds("\"٣\" string:notMatches \"\\\\d\""));
+    assert!(STRING.holds("\"a]b\" string:matches \"a[]]b\""));
+
+    assert_eq!(
+        STRING.values("(\"John Smith\" \"(\\\\w+) (\\\\w+)\" \"\\\\2, \\\\1\") string:replace ?r"),
+        vec![plain("Smith, John")]
+    );
+    assert_eq!(STRING.values("(\"a.b.c\" \"\\\\.\" \"/\") string:replace ?r"), vec![plain("a/b/c")]);
+    assert_eq!(STRING.values("(\"price 5\" \"price\" \"$USD\") string:replace ?r"), vec![plain("$USD 5")]);
+    assert_eq!(STRING.values("(\"5\" \"(\\\\d)\" \"\\\\$\\\\1\") string:replace ?r"), vec![plain("$5")]);
+    assert_eq!(STRING.values("(\"id=42;\" \"id=([0-9]+)\") string:scrape ?r"), vec![plain("42")]);
+    assert!(STRING.values("(\"nothing\" \"id=([0-9]+)\") string:scrape ?r").is_empty());
+}
+
+#[test]
+fn test_unsupported_patterns_are_invalid_arguments() {
+    let mut engine = STRING.engine("{ \"aa\" string:matches \"(a)\\\\1\" } => { :result :is true } .");
+    match engine.saturate() {
+        Err(Error::BuiltinError(BuiltinError::InvalidArgument { builtin, .. })) => {
+            assert_eq!(builtin, "http://www.w3.org/2000/10/swap/string#matches");
+        }
+        other => panic!("expected an invalid argument, got {:?}", other),
+    }
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use common::{derived_results, plain, typed, Fixture};
+use n3proof::{BuiltinError, Clock, Error, FixedClock, Program, Term};
+
+const TIME: Fixture = Fixture::new(
+    "@prefix math: <http://www.w3.org/2000/10/swap/math#> .\n\
+     @prefix time: <http://www.w3.org/2000/10/swap/time#> .\n",
+);
+
+/// A clock that is an hour later every time it is read
+#[derive(Debug, Default)]
+struct HourlyClock {
+    readings: AtomicI64,
+}
+
+impl Clock for HourlyClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        let hours = self.readings.fetch_add(1, Ordering::SeqCst);
+        DateTime::parse_from_rfc3339("2026-10-16T00:00:00Z").unwrap() + TimeDelta::hours(hours)
+    }
+}
+
+#[test]
+fn test_components() {
+    let moment = "\"2026-10-16T09:05:07.25+02:00\"^^xsd:dateTime";
+    assert_eq!(TIME.result(&format!("{} time:year ?r", moment)), typed("2026", "integer"));
+    assert_eq!(TIME.result(&format!("{} time:month ?r", moment)), typed("10", "integer"));
+    assert_eq!(TIME.result(&format!("{} time:day ?r", moment)), typed("16", "integer"));
+    assert_eq!(TIME.result(&format!("{} time:hour ?r", moment)), typed("9", "integer"));
+    assert_eq!(TIME.result(&format!("{} time:minute ?r", moment)), typed("5", "integer"));
+    assert_eq!(TIME.result(&format!("{} time:second ?r", moment)), typed("7.25", "decimal"));
+    assert_eq!(TIME.result(&format!("{} time:timeZone ?r", moment)), plain("+02:00"));
+    assert_eq!(TIME.result(&format!("{} time:inSeconds ?r", moment)), typed("1792134307.25", "decimal"));
+
+    assert_eq!(TIME.result("\"2026-10-16Z\"^^xsd:date time:day ?r"), typed("16", "integer"));
+    assert!(TIME.values("\"2026-10-16\"^^xsd:date time:hour ?r").is_empty());
+    assert!(TIME.values("\"2026-10-16T09:05:07\"^^xsd:dateTime time:timeZone ?r").is_empty());
+    assert_eq!(TIME.result("?r time:inSeconds 0"), typed("1970-01-01T00:00:00Z", "dateTime"));
+    // Fractions of a nanosecond are truncated from decimals, and rounding a double carries into the seconds
+    assert_eq!(TIME.result("?r time:inSeconds 0.9999999995"), typed("1970-01-01T00:00:00.999999999Z", "dateTime"));
+    assert_eq!(TIME.result("?r time:inSeconds 1.9999999999e0"), typed("1970-01-01T00:00:02Z", "dateTime"));
+}
+
+#[test]
+fn test_comparisons_and_differences() {
+    assert!(TIME.holds("\"2026-10-16T09:00:00+02:00\"^^xsd:dateTime math:lessThan \"2026-10-16T08:00:00Z\"^^xsd:dateTime"));
+    assert!(TIME.holds("\"2026-10-16T00:00:00Z\"^^xsd:dateTime math:equalTo \"2026-10-16Z\"^^xsd:date"));
+    assert!(TIME.holds("\"P1Y\"^^xsd:duration math:greaterThan \"P11M\"^^xsd:duration"));
+    assert!(!TIME.holds("\"P1M\"^^xsd:duration math:greaterThan \"P30D\"^^xsd:duration"));
+
+    assert_eq!(
+        TIME.result("(\"2026-10-16T12:30:00Z\"^^xsd:dateTime \"2026-10-15T10:00:00Z\"^^xsd:dateTime) math:difference ?r"),
+        typed("P1DT2H30M", "duration")
+    );
+    assert_eq!(
+        TIME.result("(\"2026-03-01\"^^xsd:date \"2026-02-01\"^^xsd:date) math:difference ?r"),
+        typed("P28D", "duration")
+    );
+    assert_eq!(
+        TIME.result("(\"2026-01-31\"^^xsd:date \"P1M\"^^xsd:duration) math:sum ?r"),
+        typed("2026-02-28", "date")
+    );
+    assert_eq!(
+        TIME.result("(\"2026-10-16T09:00:00Z\"^^xsd:dateTime \"PT90M\"^^xsd:duration) math:difference ?r"),
+        typed("2026-10-16T07:30:00Z", "dateTime")
+    );
+    assert_eq!(
+        TIME.result("(\"P1D\"^^xsd:duration \"PT36H\"^^xsd:duration) math:sum ?r"),
+        typed("P2DT12H", "duration")
+    );
+    assert_eq!(TIME.result("(1 2) math:sum ?r"), typed("3", "integer"));
+}
+
+#[test]
+fn test_malformed_durations_are_invalid_arguments() {
+    for duration in ["PT1.12345678éS", "PT1.5.5S", "P1S", "PT", "1D"] {
+        let rule = format!("{{ (\"{}\"^^xsd:duration \"PT1S\"^^xsd:duration) math:sum ?r }} => {{ :result :is ?r }} .", duration);
+        match TIME.engine(&rule).saturate() {
+            Err(Error::BuiltinError(BuiltinError::InvalidArgument { .. })) => {}
+            other => panic!("expected {} to be an invalid argument, got {:?}", duration, other),
+        }
+    }
+    assert_eq!(
+        TIME.result("(\"PT1.1234567891S\"^^xsd:duration \"PT1S\"^^xsd:duration) math:sum ?r"),
+        typed("PT2.123456789S", "duration")
+    );
+}
+
+#[test]
+fn test_local_time_reads_the_clock_and_is_recorded() {
+    let document = ":log1 :created \"2026-10-01T00:00:00Z\"^^xsd:dateTime .\n\
+                    :log2 :created \"2026-10-15T00:00:00Z\"^^xsd:dateTime .\n\
+                    { ?log :created ?created . \"\" time:localTime ?now .\n\
+                      (?now \"P7D\"^^xsd:duration) math:difference ?cutoff . ?created math:lessThan ?cutoff }\n\
+                    => { :result :is ?log } .";
+    let mut engine = TIME.engine(document);
+    engine.set_clock(FixedClock::new(DateTime::parse_from_rfc3339("2026-10-16T09:30:00+02:00").unwrap()));
+    assert_eq!(derived_results(&mut engine), vec![TIME.term(":log1")]);
+
+    let proof = engine.get_proof();
+    let step = proof.steps.iter().find(|step| step.rule == "rule1").expect("the rule fired");
+    let reading = TIME.statement("\"\" time:localTime \"2026-10-16T09:30:00+02:00\"^^xsd:dateTime .");
+    assert!(step.evidence.contains(&reading));
+}
+
+#[test]
+fn test_local_time_is_read_once_per_run() {
+    let mut engine = TIME.engine(
+        "{ ?tick a :Tick . \"\" time:localTime ?now . \"\" time:localTime ?again } => { ?tick :at (?now ?again) } .",
+    );
+    engine.set_clock(HourlyClock::default());
+    let at = |tick: &str, hour: &str| {
+        let time = typed(&format!("2026-10-16T{}:00:00Z", hour), "dateTime");
+        let mut statement = TIME.statement(&format!("{} :at () .", tick));
+        statement.object = Term::List(vec![time.clone(), time]);
+        statement
+    };
+
+    // Both readings in a run agree, and the next run reads the clock again
+    engine.add_program(&Program::from_formula(&TIME.parse(":first a :Tick .")));
+    let derived = engine.saturate().expect("saturation should succeed");
+    assert!(derived.contains(&at(":first", "00")));
+    engine.add_program(&Program::from_formula(&TIME.parse(":second a :Tick .")));
+    let derived = engine.saturate().expect("saturation should succeed");
+    assert!(derived.contains(&at(":second", "01")));
+}
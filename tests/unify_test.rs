@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use n3proof::{parse_n3, Formula, ParseOptions, Statement, Substitution, Term};
+use oxrdf::NamedNode;
+
+fn ex(local: &str) -> Term {
+    Term::Iri(NamedNode::new(format!("http://example.org/{}", local)).unwrap())
+}
+
+fn var(name: &str) -> Term {
+    Term::Variable(name.to_string())
+}
+
+fn triple(subject: Term, predicate: Term, object: Term) -> Statement {
+    Statement { subject, predicate, object }
+}
+
+fn quoted(document: &str) -> Formula {
+    let formula = parse_n3(
+        format!("@prefix : <http://example.org/> .\n:s :p {{ {} }} .\n", document).as_bytes(),
+        ParseOptions::default(),
+    )
+    .unwrap();
+    match &formula.statements[0].object {
+        Term::Formula(quoted) => (**quoted).clone(),
+        other => panic!("expected a formula, found {}", other),
+    }
+}
+
+#[test]
+fn test_mgu_of_terms_and_lists() {
+    let a = Term::List(vec![var("x"), ex("b"), var("z")]);
+    let b = Term::List(vec![ex("a"), var("y"), var("x")]);
+
+    let mgu = Substitution::mgu(&a, &b).unwrap();
+    assert_eq!(mgu.get("x"), Some(&ex("a")));
+    assert_eq!(mgu.get("y"), Some(&ex("b")));
+    assert_eq!(mgu.get("z"), Some(&ex("a")));
+    assert_eq!(mgu.apply_term(&a), mgu.apply_term(&b));
+
+    assert!(Substitution::mgu(&ex("a"), &ex("b")).is_none());
+    assert!(Substitution::mgu(&Term::List(vec![var("x")]), &Term::List(vec![])).is_none());
+    // Occurs check
+    assert!(Substitution::mgu(&var("x"), &Term::List(vec![var("x")])).is_none());
+}
+
+#[test]
+fn test_unifier_is_idempotent() {
+    let a = triple(var("x"), ex("p"), var("y"));
+    let b = triple(var("y"), ex("p"), Term::List(vec![var("z")]));
+
+    let mgu = Substitution::new().unify_statements(&a, &b).unwrap();
+    let once = mgu.apply_statement(&a);
+    assert_eq!(once, mgu.apply_statement(&b));
+    assert_eq!(mgu.apply_statement(&once), once);
+    assert_eq!(once.subject, Term::List(vec![var("z")]));
+}
+
+#[test]
+fn test_unify_formulas_in_any_order() {
+    let a = quoted("?x :knows ?y . ?y :name \"Bob\" .");
+    let b = quoted(":bob :name ?n . :alice :knows :bob .");
+
+    let unifier = Substitution::new().unify_formulas(&a, &b).unwrap();
+    assert_eq!(unifier.get("x"), Some(&ex("alice")));
+    assert_eq!(unifier.get("y"), Some(&ex("bob")));
+    assert_eq!(unifier.apply_formula(&a), unifier.apply_formula(&b));
+
+    let nested_a = triple(var("s"), ex("says"), Term::Formula(Arc::new(a)));
+    let nested_b = triple(ex("carol"), ex("says"), Term::Formula(Arc::new(b)));
+    let unifier = Substitution::new().unify_statements(&nested_a, &nested_b).unwrap();
+    assert_eq!(unifier.get("s"), Some(&ex("carol")));
+    assert_eq!(unifier.get("x"), Some(&ex("alice")));
+
+    let c = quoted(":bob :name ?n .");
+    assert!(Substitution::new().unify_formulas(&quoted("?x :knows ?y . ?y :name \"Bob\" ."), &c).is_none());
+}
+
+#[test]
+fn test_matching_is_one_way() {
+    let pattern = triple(var("x"), ex("p"), var("x"));
+    assert!(Substitution::new()
+        .match_statement(&pattern, &triple(ex("a"), ex("p"), ex("a")))
+        .is_some());
+    assert!(Substitution::new()
+        .match_statement(&pattern, &triple(ex("a"), ex("p"), ex("b")))
+        .is_none());
+
+    // Variables in the target are data, and a constant pattern does not bind them
+    assert!(Substitution::new().match_term(&ex("a"), &var("y")).is_none());
+    let matched = Substitution::new().match_term(&Term::List(vec![var("x")]), &Term::List(vec![var("x")])).unwrap();
+    assert_eq!(matched.get("x"), Some(&var("x")));
+}
+
+#[test]
+fn test_compose_and_apply_formula() {
+    let first: Substitution = vec![("x".to_string(), var("y"))].into_iter().collect();
+    let second: Substitution = vec![("y".to_string(), ex("a")), ("z".to_string(), ex("b"))].into_iter().collect();
+
+    let composed = first.compose(&second);
+    let term = Term::List(vec![var("x"), var("y"), var("z")]);
+    assert_eq!(composed.apply_term(&term), second.apply_term(&first.apply_term(&term)));
+    assert_eq!(composed.to_string(), "{?x -> <http://example.org/a>, ?y -> <http://example.org/a>, ?z -> <http://example.org/b>}");
+
+    let mut formula = Formula::new();
+    formula.add_statement(triple(var("x"), ex("p"), var("w")));
+    formula.add_universal_var("x");
+    formula.add_universal_var("w");
+    let mut renaming = Substitution::new();
+    renaming.bind("x", ex("a"));
+    renaming.bind("w", var("v"));
+
+    let applied = renaming.apply_formula(&formula);
+    assert_eq!(applied.statements[0], triple(ex("a"), ex("p"), var("v")));
+    assert_eq!(applied.universal_vars.len(), 1);
+    assert!(applied.universal_vars.contains("v"));
+}